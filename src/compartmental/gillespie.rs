@@ -0,0 +1,261 @@
+use rand::{distributions::WeightedIndex, rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Exp};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::Engine,
+    entity::EntityId,
+    network::{Graph, Network},
+    simulator::Simulator,
+    statistics::DataFrame,
+    util::sample_poisson,
+    CONFIG,
+};
+
+use super::{
+    model::{State, DEAD, EXPOSED, INFECTIOUS, RECOVERED, SUSCEPTIBLE},
+    CompartmentalModel,
+};
+
+/// Parameters of the Gillespie engine.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GillespieConfig {
+    pub model: CompartmentalModel, // Transitions between the compartments.
+    /// Length of a tau-leaping step in ticks. Every event is simulated exactly if None,
+    /// which becomes slow for large populations.
+    #[serde(default)]
+    pub tau: Option<f32>,
+    /// Contact graph over the population. Infections spread along its edges instead of
+    /// through a well-mixed population if set. Requires tau, as every exact event would
+    /// have to recompute the rates of all entities and their contacts.
+    #[serde(default)]
+    pub network: Option<Graph>,
+}
+
+impl GillespieConfig {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(tau) = self.tau {
+            if !(tau.is_finite() && tau > 0.0) {
+                return Err(format!("the tau-leaping step {} has to be positive", tau).into());
+            }
+        } else if self.network.is_some() {
+            return Err("a Gillespie network requires a tau-leaping step".into());
+        }
+        Ok(())
+    }
+}
+
+/// Transition as (rate, entity, source compartment, target compartment).
+/// Entities are only tracked on a network, in a well-mixed population
+/// any entity of the source compartment makes the transition.
+type Event = (f64, Option<EntityId>, usize, usize);
+
+/// Stochastic engine simulating the compartmental model on a well-mixed or network population,
+/// either event by event (Gillespie) or by tau-leaping.
+pub struct Gillespie {
+    config: GillespieConfig,
+    state: State,   // Whole numbers of entities per compartment.
+    initial: State, // State of the initial population.
+    network: Option<Network>,
+    compartments: Vec<usize>, // Compartment of every entity, only tracked on a network.
+    time: u32,
+    rng: StdRng,
+}
+
+impl Gillespie {
+    /// Starts from the same initial population as the spatial simulator would.
+    pub fn new(config: GillespieConfig) -> Gillespie {
+        let mut rng = StdRng::from_entropy();
+        let population = Simulator::initial_population(&mut rng);
+        let initial = CompartmentalModel::initial_state(&population);
+
+        // On a network, the entities are the nodes of the graph.
+        let network = config
+            .network
+            .as_ref()
+            .map(|graph| Network::new(graph, population.len(), &mut rng));
+        let compartments = match network {
+            Some(_) => population
+                .iter()
+                .map(CompartmentalModel::compartment)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Gillespie {
+            config,
+            state: initial,
+            initial,
+            network,
+            compartments,
+            time: 0,
+            rng,
+        }
+    }
+
+    /// Solves the configured compartmental model from the initial state, for comparison.
+    pub fn reference(&self) -> Option<DataFrame> {
+        CONFIG
+            .core
+            .compartmental
+            .as_ref()
+            .map(|model| model.solve_from(self.initial))
+    }
+
+    /// Possible transitions at the given time.
+    fn events(&self, time: f64) -> Vec<Event> {
+        let network = match &self.network {
+            Some(network) => network,
+            None => {
+                return self
+                    .config
+                    .model
+                    .transitions(time, &self.state)
+                    .iter()
+                    .map(|(rate, source, target)| (*rate, None, *source, *target))
+                    .collect()
+            }
+        };
+
+        let rates = self.config.model.rates(time);
+        // Transmission is spread over the contacts, an infectious entity of mean degree
+        // infects as many susceptible entities as in a well-mixed population.
+        let mean_degree = network.mean_degree() as f64;
+        let per_contact = if mean_degree > 0.0 {
+            rates.transmission / mean_degree
+        } else {
+            0.0
+        };
+        let infected = match rates.onset {
+            Some(_) => EXPOSED,
+            None => INFECTIOUS,
+        };
+
+        let mut events = Vec::with_capacity(2 * self.compartments.len());
+        for (entity, &compartment) in self.compartments.iter().enumerate() {
+            let mut event = |rate, target| events.push((rate, Some(entity), compartment, target));
+            match compartment {
+                SUSCEPTIBLE => {
+                    let infectious = network
+                        .neighbours(entity)
+                        .iter()
+                        .filter(|neighbour| self.compartments[**neighbour] == INFECTIOUS)
+                        .count();
+                    event(per_contact * infectious as f64, infected);
+                }
+                EXPOSED => event(rates.onset.unwrap_or(0.0), INFECTIOUS),
+                INFECTIOUS => {
+                    event(rates.removal * (1.0 - rates.fatality), RECOVERED);
+                    event(rates.removal * rates.fatality, DEAD);
+                }
+                RECOVERED => event(rates.waning, SUSCEPTIBLE),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Moves the given number of entities from the source to the target compartment.
+    fn transfer(&mut self, (_, entity, source, target): Event, count: f64) {
+        self.state[source] -= count;
+        self.state[target] += count;
+        if let Some(entity) = entity {
+            self.compartments[entity] = target;
+        }
+    }
+
+    /// Simulates the events of the current tick one by one.
+    /// Only used in a well-mixed population, which has a constant number of events.
+    fn step_exact(&mut self) {
+        let end = (self.time + 1) as f64;
+        let mut now = self.time as f64;
+
+        loop {
+            let events = self.events(now);
+            let total: f64 = events.iter().map(|(rate, ..)| rate).sum();
+            if total <= 0.0 {
+                return;
+            }
+
+            // Rates are constant within a tick, the waiting time until the next event is exponential.
+            now += Exp::new(total).unwrap().sample(&mut self.rng);
+            if now >= end {
+                return;
+            }
+
+            // Events without a positive rate are never chosen.
+            let index = WeightedIndex::new(events.iter().map(|(rate, ..)| *rate))
+                .unwrap()
+                .sample(&mut self.rng);
+            self.transfer(events[index], 1.0);
+        }
+    }
+
+    /// Simulates the current tick in leaps of tau, with Poisson distributed numbers of events.
+    /// On a network, every entity makes at most one transition per leap instead.
+    fn step_leaping(&mut self, tau: f64) {
+        let end = (self.time + 1) as f64;
+        let mut now = self.time as f64;
+
+        while now < end {
+            let leap = tau.min(end - now);
+            let events = self.events(now);
+
+            if self.network.is_some() {
+                let mut transitions = Vec::new();
+                for events in events.chunk_by(|a, b| a.1 == b.1) {
+                    let total: f64 = events.iter().map(|(rate, ..)| rate).sum();
+                    if self.rng.gen::<f64>() < 1.0 - (-total * leap).exp() {
+                        let index = WeightedIndex::new(events.iter().map(|(rate, ..)| *rate))
+                            .unwrap()
+                            .sample(&mut self.rng);
+                        transitions.push(events[index]);
+                    }
+                }
+
+                // All transitions of a leap follow from the state at its start.
+                for event in transitions {
+                    self.transfer(event, 1.0);
+                }
+            } else {
+                for event in events {
+                    let events = sample_poisson(event.0 * leap, &mut self.rng) as f64;
+                    // A compartment can't lose more entities than it holds.
+                    let events = events.min(self.state[event.2]);
+                    self.transfer(event, events);
+                }
+            }
+
+            now += leap;
+        }
+    }
+}
+
+impl Engine for Gillespie {
+    fn step(&mut self) {
+        match self.config.tau {
+            Some(tau) => self.step_leaping(tau as f64),
+            None => self.step_exact(),
+        }
+
+        self.time += 1;
+    }
+
+    fn done(&self) -> bool {
+        self.time >= CONFIG.core.time_limit
+    }
+
+    fn current_time(&self) -> u32 {
+        self.time
+    }
+
+    fn push_data(&self, dataframe: &mut DataFrame) {
+        dataframe.push_compartments(
+            self.time,
+            self.state[SUSCEPTIBLE] as u32,
+            (self.state[EXPOSED] + self.state[INFECTIOUS]) as u32,
+            self.state[RECOVERED] as u32,
+            self.state[DEAD] as u32,
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod gillespie;
+mod model;
+
+pub use gillespie::{Gillespie, GillespieConfig};
+pub use model::CompartmentalModel;
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entity::{Entity, InfectionStatus},
+    statistics::DataFrame,
+    CONFIG,
+};
+
+/// Deterministic compartmental model, solved alongside the simulation for comparison.
+/// Starts from the initial population of the simulation, and uses the infected and recovered periods
+/// and seasonality of the configuration. Entities lose their immunity after the recovered period, as in the simulation.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum CompartmentalModel {
+    Sir {
+        basic_reproduction: f32, // Secondary infections of a single infection in a susceptible population.
+        #[serde(default)]
+        fatality: f32, // Fraction of infections ending in death.
+    },
+    /// Infected entities pass through a latent period before becoming infectious.
+    /// The latent period is part of the infected period, so exposed entities are reported as infected.
+    Seir {
+        basic_reproduction: f32,
+        #[serde(default)]
+        fatality: f32,
+        latent_period: u32, // [ticks]
+    },
+}
+
+/// Sizes of the compartments: susceptible, exposed, infectious, recovered and dead.
+pub(super) type State = [f64; 5];
+
+pub(super) const SUSCEPTIBLE: usize = 0;
+pub(super) const EXPOSED: usize = 1;
+pub(super) const INFECTIOUS: usize = 2;
+pub(super) const RECOVERED: usize = 3;
+pub(super) const DEAD: usize = 4;
+
+/// Number of Runge-Kutta steps per tick.
+const STEPS_PER_TICK: u32 = 4;
+
+/// Per capita rates of the model at a given time, in events per tick.
+pub(super) struct Rates {
+    pub transmission: f64, // Infections caused by an infectious entity in a susceptible population.
+    pub onset: Option<f64>, // Exposed entities becoming infectious, None without a latent period.
+    pub removal: f64,      // Infectious entities recovering or dying.
+    pub fatality: f64,     // Fraction of removals ending in death.
+    pub waning: f64,       // Recovered entities becoming susceptible again.
+}
+
+impl CompartmentalModel {
+    /// Per capita rates at the given time.
+    pub(super) fn rates(&self, time: f64) -> Rates {
+        let (basic_reproduction, fatality, latent_period) = match self {
+            CompartmentalModel::Sir {
+                basic_reproduction,
+                fatality,
+            } => (*basic_reproduction, *fatality, 0),
+            CompartmentalModel::Seir {
+                basic_reproduction,
+                fatality,
+                latent_period,
+            } => (*basic_reproduction, *fatality, *latent_period),
+        };
+
+        let infectious_period = CONFIG
+            .core
+            .infected_period
+            .saturating_sub(latent_period)
+            .max(1) as f64;
+        let removal = 1.0 / infectious_period;
+
+        Rates {
+            transmission: basic_reproduction as f64
+                * removal
+                * CONFIG.seasonal_factor(time as u32) as f64,
+            onset: (latent_period > 0).then(|| 1.0 / latent_period as f64),
+            removal,
+            fatality: fatality as f64,
+            waning: 1.0 / CONFIG.core.recovered_period.max(1) as f64,
+        }
+    }
+
+    /// Rates of the transitions between the compartments at the given time,
+    /// as (rate, source compartment, target compartment).
+    pub(super) fn transitions(&self, time: f64, state: &State) -> [(f64, usize, usize); 5] {
+        Self::flows(&self.rates(time), state)
+    }
+
+    /// Rates of the transitions between the compartments under the given per capita rates.
+    fn flows(rates: &Rates, state: &State) -> [(f64, usize, usize); 5] {
+        let [susceptible, exposed, infectious, recovered, _] = *state;
+        let population = susceptible + exposed + infectious + recovered;
+
+        let infections = rates.transmission * susceptible * infectious / population.max(1.0);
+        let removals = rates.removal * infectious;
+
+        // Without a latent period, infections become infectious immediately.
+        let (infection, onset) = match rates.onset {
+            None => (
+                (infections, SUSCEPTIBLE, INFECTIOUS),
+                (0.0, EXPOSED, INFECTIOUS),
+            ),
+            Some(onset) => (
+                (infections, SUSCEPTIBLE, EXPOSED),
+                (onset * exposed, EXPOSED, INFECTIOUS),
+            ),
+        };
+
+        [
+            infection,
+            onset,
+            (removals * (1.0 - rates.fatality), INFECTIOUS, RECOVERED),
+            (removals * rates.fatality, INFECTIOUS, DEAD),
+            (rates.waning * recovered, RECOVERED, SUSCEPTIBLE),
+        ]
+    }
+
+    /// Rates of change of the compartments at the given time.
+    fn derivative(rates: &impl Fn(f64) -> Rates, time: f64, state: &State) -> State {
+        let mut derivative = [0.0; 5];
+        for (rate, source, target) in Self::flows(&rates(time), state) {
+            derivative[source] -= rate;
+            derivative[target] += rate;
+        }
+        derivative
+    }
+
+    /// Advances the state by a single fourth order Runge-Kutta step,
+    /// with the per capita rates given as a function of time.
+    fn step(rates: &impl Fn(f64) -> Rates, time: f64, state: &State, step: f64) -> State {
+        let add = |state: &State, rates: &State, factor: f64| -> State {
+            let mut result = *state;
+            for (value, rate) in result.iter_mut().zip(rates) {
+                *value += rate * factor;
+            }
+            result
+        };
+
+        let k1 = Self::derivative(rates, time, state);
+        let k2 = Self::derivative(rates, time + step / 2.0, &add(state, &k1, step / 2.0));
+        let k3 = Self::derivative(rates, time + step / 2.0, &add(state, &k2, step / 2.0));
+        let k4 = Self::derivative(rates, time + step, &add(state, &k3, step));
+
+        let mut result = *state;
+        for i in 0..result.len() {
+            result[i] += step / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+        }
+        result
+    }
+
+    /// Returns the compartment of the given entity.
+    /// Infected entities are infectious, the simulation has no separate latent state.
+    pub(super) fn compartment(entity: &Entity) -> usize {
+        match entity.status() {
+            InfectionStatus::Susceptible => SUSCEPTIBLE,
+            InfectionStatus::Infected(_) => INFECTIOUS,
+            InfectionStatus::Recovered(_) => RECOVERED,
+            InfectionStatus::Dead => DEAD,
+        }
+    }
+
+    /// Returns the sizes of the compartments of the given population.
+    pub(super) fn initial_state(population: &[Entity]) -> State {
+        let mut state = [0.0; 5];
+        for entity in population {
+            state[Self::compartment(entity)] += 1.0;
+        }
+        state
+    }
+
+    /// Solves the model over the time limit of the simulation,
+    /// starting from the compartments of the given initial population.
+    pub fn solve(&self, population: &[Entity]) -> DataFrame {
+        self.solve_from(Self::initial_state(population))
+    }
+
+    /// Solves the model over the time limit of the simulation, starting from the given state.
+    pub(super) fn solve_from(&self, mut state: State) -> DataFrame {
+        let mut dataframe = DataFrame::new(CONFIG.core.time_limit as usize + 1);
+        let step = 1.0 / STEPS_PER_TICK as f64;
+        let rates = |time| self.rates(time);
+
+        for time in 0..=CONFIG.core.time_limit {
+            let [susceptible, exposed, infectious, recovered, dead] = state;
+            dataframe.push_compartments(
+                time,
+                susceptible.round() as u32,
+                (exposed + infectious).round() as u32,
+                recovered.round() as u32,
+                dead.round() as u32,
+            );
+
+            for substep in 0..STEPS_PER_TICK {
+                state = Self::step(&rates, time as f64 + substep as f64 * step, &state, step);
+            }
+        }
+
+        dataframe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(rates: impl Fn(f64) -> Rates, mut state: State, ticks: u32) -> Vec<State> {
+        let step = 1.0 / STEPS_PER_TICK as f64;
+        let mut states = vec![state];
+        for tick in 0..ticks * STEPS_PER_TICK {
+            state = CompartmentalModel::step(&rates, tick as f64 * step, &state, step);
+            states.push(state);
+        }
+        states
+    }
+
+    #[test]
+    fn conserves_the_population() {
+        let rates = |time: f64| Rates {
+            transmission: 0.3 * (1.0 + 0.5 * (time / 20.0).sin()),
+            onset: Some(0.2),
+            removal: 0.1,
+            fatality: 0.05,
+            waning: 0.02,
+        };
+
+        for state in solve(rates, [990.0, 5.0, 5.0, 0.0, 0.0], 500) {
+            assert!((state.iter().sum::<f64>() - 1000.0).abs() < 1e-6);
+            assert!(state.iter().all(|&size| size > -1e-9));
+        }
+    }
+
+    #[test]
+    fn reaches_the_final_size_of_sir() {
+        let basic_reproduction = 2.0;
+        let rates = |_| Rates {
+            transmission: basic_reproduction * 0.1,
+            onset: None,
+            removal: 0.1,
+            fatality: 0.0,
+            waning: 0.0,
+        };
+
+        // The final size z solves z = 1 - exp(-R0 z).
+        let mut final_size = 0.5;
+        for _ in 0..100 {
+            final_size = 1.0 - f64::exp(-basic_reproduction * final_size);
+        }
+
+        let population = 1_000_000.0;
+        let states = solve(rates, [population - 1.0, 0.0, 1.0, 0.0, 0.0], 2000);
+        let [_, _, infectious, recovered, _] = *states.last().unwrap();
+
+        assert!(infectious < 1e-3);
+        assert!((recovered / population - final_size).abs() < 1e-3);
+    }
+}
@@ -1,57 +1,195 @@
 use std::fs;
 
-use rand::{rngs::StdRng, Rng};
+use rand::{prelude::Distribution, rngs::StdRng};
+use rand_distr::WeightedAliasIndex;
+
+use crate::entity::Sex;
 
 use super::age_pdf::AgePDF;
 
-// https://en.wikipedia.org/wiki/Pseudo-random_number_sampling
-// Given a cumulative probability distribution f, the intervals are
-// [0, f(1)), [f(1), f(2)), ..., [ff(n-1), f(n))
+// https://en.wikipedia.org/wiki/Alias_method
+// The alias table is built once in O(n), afterwards every sample is O(1),
+// regardless of how many (age, sex) cells the distribution has.
 pub struct AgeDistribution {
-    intervals: Vec<(f32, f32)>,
+    alias: WeightedAliasIndex<f32>,
+    cells: Vec<(u8, Sex)>,
 }
 
 impl AgeDistribution {
     /// Load a distribution from a file.
     /// https://www.populationpyramid.net/
-    /// Loads the csv distribution as a pdf.
-    /// Generates the intervals required for sampling.
+    /// Loads the csv distribution as a joint age/sex pdf.
+    /// Generates the alias table required for sampling.
     pub fn load(name: &'static str) -> Result<AgeDistribution, Box<dyn std::error::Error>> {
-        let ages_csv = fs::read_to_string(format!("./configurations/{}/demographic.csv", name))?;
-        let age_pdf = AgePDF::load(ages_csv)?;
-        let mut intervals = Vec::new();
+        let path = format!("./configurations/{}/demographic.csv", name);
+        let ages_csv = fs::read_to_string(&path)?;
+        let age_pdf = AgePDF::load(ages_csv).map_err(|e| format!("{}: {}", path, e))?;
+
+        AgeDistribution::from_pdf(&age_pdf)
+    }
+
+    /// Builds the alias table from the cells of the given pdf.
+    pub fn from_pdf(age_pdf: &AgePDF) -> Result<AgeDistribution, Box<dyn std::error::Error>> {
+        let (cells, weights): (Vec<(u8, Sex)>, Vec<f32>) = age_pdf
+            .cells()
+            .into_iter()
+            .map(|(age, sex, chance)| ((age, sex), chance))
+            .unzip();
+
+        Ok(AgeDistribution {
+            alias: WeightedAliasIndex::new(weights)?,
+            cells,
+        })
+    }
+
+    /// Sample an age and sex from the distribution.
+    /// See the wikipedia article linked on top for details.
+    pub fn sample(&self, rng: &mut StdRng) -> (u8, Sex) {
+        self.cells[self.alias.sample(rng)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    const SAMPLES: usize = 200_000;
+
+    /// The interval sampler the alias table replaced.
+    /// Given the cumulative distribution f, the intervals are [0, f(1)), [f(1), f(2)), ..., [f(n-1), f(n)),
+    /// each belonging to one (age, sex) cell.
+    struct CdfSampler {
+        intervals: Vec<(f32, f32)>,
+        cells: Vec<(u8, Sex)>,
+    }
+
+    impl CdfSampler {
+        fn new(age_pdf: &AgePDF) -> CdfSampler {
+            let mut intervals = Vec::new();
+            let mut cells = Vec::new();
+            let mut lower_bound = 0.0;
+
+            for (age, sex, chance) in age_pdf.cells() {
+                intervals.push((lower_bound, lower_bound + chance));
+                cells.push((age, sex));
+                lower_bound += chance;
+            }
 
-        for i in 1..(age_pdf.age_chances.len() - 1) {
-            // Use the sum_to function to 'convert' the pdf to a cdf
-            let lower_bound = AgeDistribution::sum_to(&age_pdf, i - 1);
-            let upper_bound = AgeDistribution::sum_to(&age_pdf, i);
+            // Floating point errors might leave the cdf slightly below 1.0,
+            // the last non-empty interval covers that gap.
+            if let Some(last) = intervals
+                .iter_mut()
+                .rev()
+                .find(|(lower, upper)| lower < upper)
+            {
+                last.1 = 1.0;
+            }
 
-            intervals.push((lower_bound, upper_bound));
+            CdfSampler { intervals, cells }
         }
 
-        Ok(AgeDistribution { intervals })
+        fn sample(&self, rng: &mut StdRng) -> (u8, Sex) {
+            let rng_value: f32 = rng.gen();
+            self.intervals
+                .iter()
+                .position(|(lower, upper)| *lower <= rng_value && rng_value < *upper)
+                .map_or(self.cells[0], |index| self.cells[index])
+        }
     }
 
-    /// Helper function to sum up the probabilities of the AgePDF
-    fn sum_to(age_pdf: &AgePDF, i: usize) -> f32 {
-        let mut sum = 0.0;
-        for j in 0..i {
-            sum += age_pdf.age_chances[j];
+    /// Relative frequency of every cell of the pdf among the samples.
+    fn frequencies(
+        age_pdf: &AgePDF,
+        sample: impl Fn(&mut StdRng) -> (u8, Sex),
+        seed: u64,
+    ) -> Vec<f64> {
+        let cells = age_pdf.cells();
+        let mut counts = vec![0usize; cells.len()];
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..SAMPLES {
+            let (age, sex) = sample(&mut rng);
+            let index = cells
+                .iter()
+                .position(|cell| cell.0 == age && cell.1 == sex)
+                .unwrap();
+            counts[index] += 1;
         }
-        sum
+
+        counts
+            .iter()
+            .map(|count| *count as f64 / SAMPLES as f64)
+            .collect()
     }
 
-    /// Sample an age from the distribution.
-    /// See the wikipedia article linked on top for details.
-    pub fn sample(&self, rng: &mut StdRng) -> u8 {
-        let rng_value = rng.gen();
-        for i in 0..self.intervals.len() {
-            let interval = &self.intervals[i];
-            if interval.0 <= rng_value && rng_value < interval.1 {
-                return i as u8;
+    /// Pearson's chi-square statistic of the sampled cell counts against the pdf,
+    /// together with the degrees of freedom.
+    fn chi_square(age_pdf: &AgePDF, sample: impl Fn(&mut StdRng) -> (u8, Sex)) -> (f64, usize) {
+        let cells = age_pdf.cells();
+        let counts: Vec<f64> = frequencies(age_pdf, sample, 7)
+            .iter()
+            .map(|frequency| frequency * SAMPLES as f64)
+            .collect();
+
+        let mut statistic = 0.0;
+        let mut degrees = 0;
+        for (count, (_, _, chance)) in counts.iter().zip(&cells) {
+            let expected = *chance as f64 * SAMPLES as f64;
+            if expected == 0.0 {
+                assert_eq!(*count, 0.0, "sampled a cell without population");
+                continue;
             }
+
+            statistic += (count - expected).powi(2) / expected;
+            degrees += 1;
         }
 
-        0
+        (statistic, degrees - 1)
+    }
+
+    #[test]
+    fn samples_match_the_loaded_pdf() {
+        let csv = "Age,M,F\n0-14,150,140\n15-39,250,260\n40-64,200,210\n65+,90,120\n";
+        let age_pdf = AgePDF::load(csv.to_string()).unwrap();
+        let distribution = AgeDistribution::from_pdf(&age_pdf).unwrap();
+
+        let (statistic, degrees) = chi_square(&age_pdf, |rng| distribution.sample(rng));
+
+        // Six standard deviations above the mean of the chi-square distribution.
+        let limit = degrees as f64 + 6.0 * (2.0 * degrees as f64).sqrt();
+        assert!(
+            statistic < limit,
+            "chi-square {} exceeds {} with {} degrees of freedom",
+            statistic,
+            limit,
+            degrees
+        );
+    }
+
+    #[test]
+    fn alias_table_is_equivalent_to_the_cdf_sampler() {
+        let csv = "Age,M,F\n0-4,40,38\n5-17,120,115\n18-29,150,160\n30-49,260,250\n50-69,210,230\n70+,80,130\n";
+        let age_pdf = AgePDF::load(csv.to_string()).unwrap();
+        let alias = AgeDistribution::from_pdf(&age_pdf).unwrap();
+        let cdf = CdfSampler::new(&age_pdf);
+
+        let alias_frequencies = frequencies(&age_pdf, |rng| alias.sample(rng), 1);
+        let cdf_frequencies = frequencies(&age_pdf, |rng| cdf.sample(rng), 2);
+
+        for (index, (a, b)) in alias_frequencies.iter().zip(&cdf_frequencies).enumerate() {
+            // Five standard errors of the difference of two sampled frequencies.
+            let p = (a + b) / 2.0;
+            let tolerance = 5.0 * (2.0 * p * (1.0 - p) / SAMPLES as f64).sqrt() + 1e-9;
+            assert!(
+                (a - b).abs() <= tolerance,
+                "cell {}: alias {} and cdf {} differ by more than {}",
+                index,
+                a,
+                b,
+                tolerance
+            );
+        }
     }
 }
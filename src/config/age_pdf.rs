@@ -1,49 +1,285 @@
-const BUCKET_COUNT: usize = 20;
+use crate::entity::Sex;
+
+/// Open ended buckets such as 85+ are spread evenly up to this age.
+const MAX_AGE: u8 = 100;
 
 pub struct AgePDF {
-    pub(super) age_chances: [f32; 101],
+    pub(super) male_chances: Vec<f32>,
+    pub(super) female_chances: Vec<f32>,
+}
+
+/// Helper function to build an error message pointing at a line of the csv.
+fn line_error(line_number: usize, message: String) -> Box<dyn std::error::Error> {
+    format!("line {}: {}", line_number, message).into()
+}
+
+/// Parses an age bucket label into an inclusive (first, last) age range.
+/// Supported labels are ranges (0-4), open ended buckets (85+) and single ages (37).
+fn parse_bucket(label: &str) -> Result<(u8, u8), String> {
+    let parse_age = |age: &str| {
+        age.trim()
+            .parse::<u8>()
+            .map_err(|_| format!("invalid age '{}' in bucket '{}'", age.trim(), label))
+    };
+
+    if let Some(first) = label.strip_suffix('+') {
+        let first = parse_age(first)?;
+        Ok((first, first.max(MAX_AGE)))
+    } else if let Some((first, last)) = label.split_once('-') {
+        let (first, last) = (parse_age(first)?, parse_age(last)?);
+        if first > last {
+            return Err(format!("bucket '{}' ends before it starts", label));
+        }
+        Ok((first, last))
+    } else {
+        let age = parse_age(label)?;
+        Ok((age, age))
+    }
+}
+
+/// Parses a population count, which has to be a finite, non-negative number.
+fn parse_count(count: &str, column: &str) -> Result<f32, String> {
+    match count.trim().parse::<f32>() {
+        Ok(count) if count.is_finite() && count >= 0.0 => Ok(count),
+        _ => Err(format!("invalid {} count '{}'", column, count.trim())),
+    }
 }
 
 impl AgePDF {
     /// Load a distribution from a file.
-    /// Creates a pdf from the csv files absolute population numbers.
-    /// Ignores male/female for now as the simulator ignores it as well.
-    /// populationpyramid.net csv files use intervals such as 0-4, 5-9 etc.
-    /// This function unravels these intervals, evenly dividing them.
-    /// Treats 100+ as just a single bucket of 100 year olds.
+    /// Creates a joint age/sex pdf from the csv files absolute population numbers.
+    /// The chances of both sexes together sum up to 1.
+    /// The csv needs a header naming the Age, M and F columns, their order does not matter.
+    /// Buckets can be labeled as ranges (0-4), open ended (85+) or single ages (37),
+    /// their population is evenly divided among the ages they contain.
+    /// Open ended buckets reach up to MAX_AGE.
+    /// An optional Total row is checked against the sum of all buckets.
     pub fn load(ages_csv: String) -> Result<AgePDF, Box<dyn std::error::Error>> {
-        let mut age_chances = [0.0; 101];
-        let mut lines = ages_csv.lines();
-        lines.next(); // Skip header
+        let mut lines = ages_csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (header_number, header) = lines.next().ok_or("demographic csv is empty")?;
+        let header: Vec<String> = header
+            .split(',')
+            .map(|column| column.trim().to_lowercase())
+            .collect();
+        let find_column = |names: &[&str]| {
+            header
+                .iter()
+                .position(|column| names.contains(&column.as_str()))
+                .ok_or_else(|| {
+                    line_error(
+                        header_number,
+                        format!("header is missing a '{}' column", names[0]),
+                    )
+                })
+        };
+        let age_column = find_column(&["age"])?;
+        let male_column = find_column(&["m", "male"])?;
+        let female_column = find_column(&["f", "female"])?;
+
+        let mut male_chances = vec![0.0; MAX_AGE as usize + 1];
+        let mut female_chances = vec![0.0; MAX_AGE as usize + 1];
+        let mut covered = vec![false; MAX_AGE as usize + 1];
 
         let mut population_total = 0.0;
+        let mut expected_total = None;
 
-        for bucket in 0..BUCKET_COUNT {
-            let line = lines.next().unwrap();
-            let parts: Vec<&str> = line.split(",").collect();
+        for (line_number, line) in lines {
+            let parts: Vec<&str> = line.split(',').collect();
+            if parts.len() != header.len() {
+                return Err(line_error(
+                    line_number,
+                    format!("expected {} columns, found {}", header.len(), parts.len()),
+                ));
+            }
 
-            let bucket_count = parts[1].parse::<u32>().unwrap() + parts[2].parse::<u32>().unwrap();
-            population_total += bucket_count as f32;
-            let bucket_count = bucket_count / 5;
+            let label = parts[age_column].trim();
+            let male_count = parse_count(parts[male_column], "male")
+                .map_err(|message| line_error(line_number, message))?;
+            let female_count = parse_count(parts[female_column], "female")
+                .map_err(|message| line_error(line_number, message))?;
 
-            age_chances[bucket as usize * 5 + 0] = bucket_count as f32;
-            age_chances[bucket as usize * 5 + 1] = bucket_count as f32;
-            age_chances[bucket as usize * 5 + 2] = bucket_count as f32;
-            age_chances[bucket as usize * 5 + 3] = bucket_count as f32;
-            age_chances[bucket as usize * 5 + 4] = bucket_count as f32;
+            if label.eq_ignore_ascii_case("total") {
+                expected_total = Some((line_number, male_count + female_count));
+                continue;
+            }
+
+            let (first, last) =
+                parse_bucket(label).map_err(|message| line_error(line_number, message))?;
+
+            if last as usize >= covered.len() {
+                covered.resize(last as usize + 1, false);
+                male_chances.resize(last as usize + 1, 0.0);
+                female_chances.resize(last as usize + 1, 0.0);
+            }
+
+            if let Some(age) = (first..=last).find(|age| covered[*age as usize]) {
+                return Err(line_error(
+                    line_number,
+                    format!(
+                        "bucket '{}' overlaps a previous bucket at age {}",
+                        label, age
+                    ),
+                ));
+            }
+
+            let bucket_size = (last - first) as f32 + 1.0;
+            for age in first..=last {
+                covered[age as usize] = true;
+                male_chances[age as usize] = male_count / bucket_size;
+                female_chances[age as usize] = female_count / bucket_size;
+            }
+
+            population_total += male_count + female_count;
         }
 
-        // Special case for 100+ group
-        let line = lines.next().unwrap();
-        let parts: Vec<&str> = line.split(",").collect();
-        let bucket_count = parts[1].parse::<u32>().unwrap() + parts[2].parse::<u32>().unwrap();
-        population_total += bucket_count as f32;
-        age_chances[100] = bucket_count as f32;
+        if population_total <= 0.0 {
+            return Err("demographic csv does not contain any population".into());
+        }
 
-        for bucket in 0..101 {
-            age_chances[bucket as usize] /= population_total;
+        if let Some((line_number, expected_total)) = expected_total {
+            // Allow for rounding in the source data
+            if (expected_total - population_total).abs() > expected_total * 1e-3 {
+                return Err(line_error(
+                    line_number,
+                    format!(
+                        "total of {} does not match the sum of all buckets ({})",
+                        expected_total, population_total
+                    ),
+                ));
+            }
         }
 
-        Ok(AgePDF { age_chances })
+        for age in 0..male_chances.len() {
+            male_chances[age] /= population_total;
+            female_chances[age] /= population_total;
+        }
+
+        Ok(AgePDF {
+            male_chances,
+            female_chances,
+        })
+    }
+
+    /// Creates a joint age/sex pdf from relative weights per year of age.
+    /// Every age is split into male_fraction males and 1 - male_fraction females.
+    pub fn from_weights(
+        weights: &[f32],
+        male_fraction: f32,
+    ) -> Result<AgePDF, Box<dyn std::error::Error>> {
+        if !(0.0..=1.0).contains(&male_fraction) {
+            return Err(format!("male fraction {} is not within [0, 1]", male_fraction).into());
+        }
+
+        if weights.len() > u8::MAX as usize + 1 {
+            return Err(format!(
+                "{} age weights given, ages end at {}",
+                weights.len(),
+                u8::MAX
+            )
+            .into());
+        }
+
+        if let Some(age) = weights.iter().position(|w| !w.is_finite() || *w < 0.0) {
+            return Err(format!("invalid weight {} for age {}", weights[age], age).into());
+        }
+
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err("age weights do not contain any population".into());
+        }
+
+        Ok(AgePDF {
+            male_chances: weights.iter().map(|w| w / total * male_fraction).collect(),
+            female_chances: weights
+                .iter()
+                .map(|w| w / total * (1.0 - male_fraction))
+                .collect(),
+        })
+    }
+
+    /// Flattens the joint distribution into (age, sex, chance) cells.
+    /// All male cells come first, followed by all female cells.
+    pub fn cells(&self) -> Vec<(u8, Sex, f32)> {
+        let male = self
+            .male_chances
+            .iter()
+            .enumerate()
+            .map(|(age, chance)| (age as u8, Sex::Male, *chance));
+        let female = self
+            .female_chances
+            .iter()
+            .enumerate()
+            .map(|(age, chance)| (age as u8, Sex::Female, *chance));
+
+        male.chain(female).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chance(chances: &[f32], age: usize) -> f32 {
+        chances.get(age).copied().unwrap_or(0.0)
+    }
+
+    #[test]
+    fn parses_arbitrary_bucket_widths() {
+        let csv = "Age,M,F\n0-4,50,50\n5,10,30\n6-19,140,0\n20+,0,720\nTotal,200,800\n";
+        let pdf = AgePDF::load(csv.to_string()).unwrap();
+
+        // Every bucket is divided evenly among its ages.
+        assert!((chance(&pdf.male_chances, 2) - 50.0 / 5.0 / 1000.0).abs() < 1e-6);
+        assert!((chance(&pdf.female_chances, 5) - 30.0 / 1000.0).abs() < 1e-6);
+        assert!((chance(&pdf.male_chances, 19) - 10.0 / 1000.0).abs() < 1e-6);
+        assert_eq!(chance(&pdf.female_chances, 19), 0.0);
+        // Open ended buckets reach up to MAX_AGE.
+        let open_ages = (MAX_AGE - 20) as f32 + 1.0;
+        assert!(
+            (chance(&pdf.female_chances, MAX_AGE as usize) - 720.0 / open_ages / 1000.0).abs()
+                < 1e-6
+        );
+
+        let total: f32 = pdf.cells().iter().map(|(_, _, chance)| chance).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn accepts_columns_in_any_order() {
+        let csv = "female,Age,male\n10,0-9,30\n";
+        let pdf = AgePDF::load(csv.to_string()).unwrap();
+
+        assert!((chance(&pdf.male_chances, 0) - 0.075).abs() < 1e-6);
+        assert!((chance(&pdf.female_chances, 0) - 0.025).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reports_malformed_rows_with_their_line() {
+        let csv = "Age,M,F\n0-4,10,10\n\n5-9,ten,10\n";
+        let error = AgePDF::load(csv.to_string()).err().unwrap().to_string();
+        assert!(error.starts_with("line 4:"), "{}", error);
+        assert!(error.contains("invalid male count 'ten'"), "{}", error);
+
+        let csv = "Age,M,F\n0-4,10\n";
+        let error = AgePDF::load(csv.to_string()).err().unwrap().to_string();
+        assert!(error.starts_with("line 2:"), "{}", error);
+
+        let csv = "Age,M,F\n0-4,10,10\n3-9,10,10\n";
+        let error = AgePDF::load(csv.to_string()).err().unwrap().to_string();
+        assert!(error.starts_with("line 3:"), "{}", error);
+        assert!(error.contains("overlaps"), "{}", error);
+    }
+
+    #[test]
+    fn reports_a_bad_total_with_its_line() {
+        let csv = "Age,M,F\n0-4,10,10\n5+,10,10\nTotal,10,10\n";
+        let error = AgePDF::load(csv.to_string()).err().unwrap().to_string();
+        assert!(error.starts_with("line 4:"), "{}", error);
+        assert!(error.contains("does not match"), "{}", error);
     }
 }
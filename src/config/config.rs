@@ -1,15 +1,27 @@
+use chrono::{Duration, NaiveDate};
+use quadtree::Positioned;
 use rand::prelude::StdRng;
 
-use crate::entity::Entity;
+use crate::{
+    engine::EngineKind,
+    entity::{Entity, EntityId, Sex},
+    network::{EdgeList, Graph},
+};
 
-use super::{age_distribution::AgeDistribution, core::ConfigCore};
+use super::{
+    age_distribution::AgeDistribution, core::ConfigCore, validate_increasing, Demographic,
+    PopulationEntry, PopulationFile,
+};
 
 pub struct Config {
     name: String,
     pub survival_chance: fn(&Entity) -> f32, // Calculates the survival chance of an entity.
-    pub infection_chance: fn(&Entity, &Entity) -> f32, // Calculates the chance entity a will infect entity b.
+    pub infection_chance: fn(&Entity, &Entity) -> f32, // Calculates the chance of entity a infecting entity b during a single tick. Should account for Entity::infectiousness.
 
-    age_distribution: AgeDistribution,
+    age_distribution: Option<AgeDistribution>, // None if the population is loaded from a population file.
+    population_entries: Option<Vec<PopulationEntry>>,
+    network_edges: Option<Vec<(EntityId, EntityId)>>, // Edges of the contact network of the engine, if loaded from an edge list file.
+    start_date: NaiveDate,
 
     // Everything that can be serialzed
     pub core: ConfigCore,
@@ -26,7 +38,116 @@ impl Config {
         infection_chance: fn(&Entity, &Entity) -> f32,
     ) -> Result<Config, Box<dyn std::error::Error>> {
         let core = ConfigCore::load(name)?;
-        let age_distribution = AgeDistribution::load(name)?;
+
+        let start_date = NaiveDate::parse_from_str(&core.start_date, "%Y-%m-%d")
+            .map_err(|e| format!("invalid start date {}: {}", core.start_date, e))?;
+        if core.ticks_per_day == 0 {
+            return Err("ticks_per_day has to be at least 1".into());
+        }
+        core.movement.validate()?;
+        core.infectiousness.validate()?;
+        core.infectiousness_variation.validate()?;
+        if let Some(masks) = &core.masks {
+            masks.validate()?;
+        }
+        if let Some(lockdown) = &core.lockdown {
+            lockdown.validate()?;
+        }
+        for test_kind in &core.test_kinds {
+            test_kind.validate()?;
+        }
+        if !core.test_kinds.is_empty() && core.test_kinds.iter().all(|kind| kind.share == 0.0) {
+            return Err("at least one test kind needs a positive share".into());
+        }
+        if let Some(seasonality) = &core.seasonality {
+            seasonality.validate()?;
+        }
+        for importation in &core.importations {
+            validate_increasing("ticks of the importation rate", &importation.rate)?;
+            if let Some(strain) = &importation.strain {
+                if !core
+                    .strains
+                    .iter()
+                    .any(|configured| &configured.name == strain)
+                {
+                    return Err(format!("imported strain {} is not configured", strain).into());
+                }
+            }
+        }
+        // Entities store their risk factors as the bits of a u32.
+        if core.risk_factors.len() > 32 {
+            return Err(format!(
+                "{} risk factors are configured, at most 32 are supported",
+                core.risk_factors.len()
+            )
+            .into());
+        }
+        for risk_factor in &core.risk_factors {
+            validate_increasing(
+                &format!("ages of the {} prevalence", risk_factor.name),
+                &risk_factor.prevalence,
+            )?;
+        }
+        if let Some(vitals) = &core.vitals {
+            validate_increasing("ages of the mortality", &vitals.mortality)?;
+        }
+        if let Some(initial_conditions) = &core.initial_conditions {
+            initial_conditions.validate(core.population_size, &core.strains)?;
+        }
+
+        if let EngineKind::Gillespie(gillespie) = &core.engine {
+            gillespie.validate()?;
+        }
+
+        // Only the graph of the engine that runs is built.
+        let graph = match &core.engine {
+            EngineKind::Spatial => core
+                .contact_network
+                .as_ref()
+                .map(|contact_network| &contact_network.graph),
+            EngineKind::Gillespie(gillespie) => gillespie.network.as_ref(),
+        };
+        let network_edges = match graph {
+            Some(graph) => {
+                graph.validate(core.population_size)?;
+                match graph {
+                    Graph::File(path) => Some(EdgeList::load(
+                        &format!("./configurations/{}/{}", name, path),
+                        core.population_size as usize,
+                    )?),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        let (age_distribution, population_entries) = match &core.population_file {
+            Some(path) => {
+                let entries = PopulationFile::load(path, core.dimensions)?;
+                if entries.len() != core.population_size as usize {
+                    return Err(format!(
+                        "{} contains {} entities, but the population size is {}",
+                        path,
+                        entries.len(),
+                        core.population_size
+                    )
+                    .into());
+                }
+
+                (None, Some(entries))
+            }
+            None => {
+                let age_distribution = match &core.demographic {
+                    Demographic::Csv => AgeDistribution::load(name)?,
+                    Demographic::Parametric {
+                        shape,
+                        male_fraction,
+                    } => AgeDistribution::from_pdf(&shape.to_pdf(*male_fraction)?)?,
+                };
+
+                (Some(age_distribution), None)
+            }
+        };
 
         Ok(Config {
             name: format!(
@@ -37,12 +158,81 @@ impl Config {
             survival_chance,
             infection_chance,
             age_distribution,
+            population_entries,
+            network_edges,
+            start_date,
             core,
         })
     }
 
-    pub fn sample_age(&self, rng: &mut StdRng) -> u8 {
-        self.age_distribution.sample(rng)
+    /// Samples an age and sex from the joint demographic distribution.
+    /// Panics if the population is loaded from a population file.
+    pub fn sample_demographic(&self, rng: &mut StdRng) -> (u8, Sex) {
+        self.age_distribution
+            .as_ref()
+            .expect("No demographic distribution, the population is loaded from a file.")
+            .sample(rng)
+    }
+
+    /// Chance of the infected entity a infecting the susceptible entity b during a single tick.
+    /// Uses the configured infection kernel, scaled by the infectiousness of a,
+    /// otherwise the infection chance function. Clamped to [0, 1].
+    pub fn contact_chance(&self, a: &Entity, b: &Entity) -> f32 {
+        let chance = match &self.core.infection_kernel {
+            Some(kernel) => kernel.chance(a.position().distance(b.position())) * a.infectiousness(),
+            None => (self.infection_chance)(a, b),
+        };
+
+        chance.clamp(0.0, 1.0)
+    }
+
+    /// Returns the simulated date at the given tick.
+    pub fn date(&self, time: u32) -> NaiveDate {
+        self.start_date + Duration::days((time / self.core.ticks_per_day) as i64)
+    }
+
+    /// Returns the number of ticks simulating a year.
+    pub fn ticks_per_year(&self) -> u32 {
+        365 * self.core.ticks_per_day
+    }
+
+    /// Returns the seasonal multiplier on the chance of infection at the given tick.
+    pub fn seasonal_factor(&self, time: u32) -> f32 {
+        self.core
+            .seasonality
+            .as_ref()
+            .map_or(1.0, |seasonality| seasonality.factor(self.date(time)))
+    }
+
+    /// Returns the index of the strain with the given name.
+    pub fn strain_index(&self, name: &str) -> Option<usize> {
+        self.core
+            .strains
+            .iter()
+            .position(|strain| strain.name == name)
+    }
+
+    /// Returns the transmissibility of the given strain, 1.0 for the base strain.
+    pub fn transmissibility(&self, strain: Option<usize>) -> f32 {
+        strain.map_or(1.0, |index| self.core.strains[index].transmissibility)
+    }
+
+    /// Returns the index of the risk factor with the given name.
+    pub fn risk_factor_index(&self, name: &str) -> Option<usize> {
+        self.core
+            .risk_factors
+            .iter()
+            .position(|risk_factor| risk_factor.name == name)
+    }
+
+    /// Returns the entries of the population file, if one is used.
+    pub fn population_entries(&self) -> Option<&Vec<PopulationEntry>> {
+        self.population_entries.as_ref()
+    }
+
+    /// Returns the edges of the contact network, if loaded from an edge list file.
+    pub fn network_edges(&self) -> Option<&Vec<(EntityId, EntityId)>> {
+        self.network_edges.as_ref()
     }
 
     pub fn name(&self) -> &String {
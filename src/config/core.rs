@@ -9,30 +9,117 @@ use ron::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::CONFIG;
+use crate::{
+    compartmental::CompartmentalModel,
+    engine::EngineKind,
+    hospital::{
+        AdmissionPolicy, CareTierConfig, HospitalConfig, NosocomialConfig, OverflowMortality,
+    },
+    interventions::{Lockdown, Masks},
+    movement::Movement,
+    network::ContactNetwork,
+    testing::{TestKind, TestingStrategy},
+    CONFIG,
+};
+
+use super::{
+    Demographic, Importation, InfectionKernel, InfectiousnessProfile, InfectiousnessVariation,
+    InitialConditions, RiskFactor, Seasonality, Strain, Vitals,
+};
 
 /// This struct contains all the simulation parameters that can be serialized/deserialized.
 #[derive(Default, Serialize, Deserialize)]
 pub struct ConfigCore {
-    pub time_limit: u32,        // Maximum number of days to simulate.
+    pub time_limit: u32, // Maximum number of ticks to simulate.
+    #[serde(default)]
+    pub engine: EngineKind, // Engine running the simulation. Defaults to the spatial simulator.
+    #[serde(default = "default_start_date")]
+    pub start_date: String, // Date of the first tick, as YYYY-MM-DD.
+    #[serde(default = "default_ticks_per_day")]
+    pub ticks_per_day: u32, // Number of ticks simulating a single day.
     pub dimensions: (u32, u32), // Simulation space dimensions.
-    pub max_velocity: f32,      // Maximum velocity of an entity.
+    pub max_velocity: f32, // Maximum velocity of an entity.
+    #[serde(default)]
+    pub movement: Movement, // How entities move. Defaults to the force model.
 
-    pub population_size: u32,   // Number of entities in the simulation.
-    pub infected_period: u32,   // Number of days an entity is infected.
-    pub recovered_period: u32,  // Number of days an entity is recovered.
-    pub infection_radius: u32,  // Radius of the infection area.
+    pub population_size: u32,  // Number of entities in the simulation.
+    pub infected_period: u32,  // Number of ticks an entity is infected.
+    pub recovered_period: u32, // Number of ticks an entity is recovered.
+    pub infection_radius: u32, // Radius of the infection area.
+    #[serde(default)]
+    pub infection_kernel: Option<InfectionKernel>, // Built-in chance of infection per contact and tick. Uses the infection chance function if None.
+    #[serde(default)]
+    pub contact_network: Option<ContactNetwork>, // Contacts along the edges of a graph instead of within the infection radius. Disabled if None.
+    #[serde(default)]
+    pub infectiousness: InfectiousnessProfile, // Infectiousness over the course of an infection. Flat by default.
+    #[serde(default)]
+    pub infectiousness_variation: InfectiousnessVariation, // Individual infectiousness, drawn on infection. Homogeneous by default.
+    #[serde(default)]
+    pub seasonality: Option<Seasonality>, // Seasonal multiplier on the chance of infection. Disabled if None.
+    #[serde(default)]
+    pub strains: Vec<Strain>, // Variants besides the base strain.
+    #[serde(default)]
+    pub importations: Vec<Importation>, // Cases imported from outside the simulated area.
     pub hospital_period: u32, // Number of days an entity is hospitalized. If the entity is infected already, the entity will only be hospitalized for the remaining infection period.
     pub hospital_capacity: u32, // Maximum number of entities that can be hospitalized at a given time.
+    #[serde(default)]
+    pub hospitals: Vec<HospitalConfig>, // Locations and capacities of the hospitals. A single central hospital with the above capacities if empty.
+    #[serde(default)]
+    pub icu: Option<CareTierConfig>, // Intensive care tier. Disabled if None.
+    #[serde(default)]
+    pub ventilator: Option<CareTierConfig>, // Ventilator tier. Disabled if None.
+    #[serde(default)]
+    pub admission_policy: AdmissionPolicy, // Triage strategy deciding who is hospitalized after a positive test.
+    #[serde(default)]
+    pub overflow_mortality: Option<OverflowMortality>, // Penalty for severe cases not admitted due to a full hospital. Disabled if None.
+    #[serde(default)]
+    pub nosocomial: Option<NosocomialConfig>, // Health-care workers and transmission inside hospitals. Disabled if None.
 
     pub initial_infected: f32, // Chance of an entity being infected at the start of the simulation.
     pub initial_mobile: f32,   // Chance of an entity being mobile at the start of the simulation.
+    #[serde(default)]
+    pub initial_conditions: Option<InitialConditions>, // Exact initial state of the population. Replaces initial_infected if set.
+    #[serde(default = "default_symptomatic_chance")]
+    pub symptomatic_chance: f32, // Chance of an infection showing symptoms.
 
-    pub tests_per_time: u32,     // Number of tests per day.
-    pub test_true_positive: f32, // Chance of a test being true positive. Implies test_false_positive = 1.0 - test_true_positive.
-    pub test_true_negative: f32, // Chance of a test being true negative. Implies test_false_negative = 1.0 - test_true_negative.
+    pub tests_per_time: u32,     // Number of tests per tick.
+    pub test_true_positive: f32, // Chance of a test being true positive. Implies test_false_positive = 1.0 - test_true_positive. Unused if test_kinds are configured.
+    pub test_true_negative: f32, // Chance of a test being true negative. Implies test_false_negative = 1.0 - test_true_negative. Unused if test_kinds are configured.
+    #[serde(default)]
+    pub testing: TestingStrategy, // Who is tested each tick. Defaults to random testing.
+    #[serde(default)]
+    pub test_kinds: Vec<TestKind>, // Kinds of tests in use. A single instant test with the above chances if empty.
 
     pub distancing: bool, // Whether or not distancing is enabled.
+    #[serde(default)]
+    pub masks: Option<Masks>, // Mask wearing and when it is in effect. Disabled if None.
+    #[serde(default)]
+    pub lockdown: Option<Lockdown>, // Mobility reduction and when it is in effect. Disabled if None.
+
+    #[serde(default)]
+    pub demographic: Demographic, // Age/sex composition of the population. Defaults to the demographic.csv.
+    #[serde(default)]
+    pub population_file: Option<String>, // Population file to load instead of generating the population.
+    #[serde(default)]
+    pub vitals: Option<Vitals>, // Births, natural deaths and ageing. The population is fixed if None.
+    #[serde(default)]
+    pub risk_factors: Vec<RiskFactor>, // Comorbidities sampled for each entity, see Entity::has_risk_factor.
+    #[serde(default)]
+    pub compartmental: Option<CompartmentalModel>, // Compartmental model solved for comparison with the simulation. Disabled if None.
+    #[serde(default)]
+    pub export_population: bool, // Whether or not the initial population is exported as a population file.
+}
+
+fn default_symptomatic_chance() -> f32 {
+    1.0
+}
+
+fn default_start_date() -> String {
+    "2020-01-01".to_string()
+}
+
+fn default_ticks_per_day() -> u32 {
+    1
 }
 
 impl ConfigCore {
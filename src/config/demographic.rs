@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use super::age_pdf::AgePDF;
+
+/// Named shapes of population pyramids.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PyramidShape {
+    Expansive,    // Many young people, steadily declining with age.
+    Stationary,   // Roughly equal cohorts until old age.
+    Constrictive, // Fewer young people, bulging middle aged cohorts.
+}
+
+/// Parametric age distributions. Ages range from 0 to max_age.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AgeShape {
+    Uniform { max_age: u8 },
+    ExponentialDecay { rate: f32, max_age: u8 }, // weight(age) = e^(-rate * age)
+    Pyramid(PyramidShape),                       // Ages 0 to 100.
+    Weights(Vec<f32>),                           // One weight per year of age, starting at age 0.
+}
+
+/// Source of the demographic composition of the population.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum Demographic {
+    #[default]
+    Csv, // Load the demographic.csv next to the core.cfg.
+    Parametric {
+        shape: AgeShape,
+        male_fraction: f32,
+    },
+}
+
+impl PyramidShape {
+    fn weight(&self, age: f32) -> f32 {
+        match self {
+            PyramidShape::Expansive => (-age / 20.0).exp(),
+            PyramidShape::Stationary => {
+                if age < 60.0 {
+                    1.0
+                } else {
+                    (-(age - 60.0) / 10.0).exp()
+                }
+            }
+            PyramidShape::Constrictive => {
+                let distance = (age - 45.0) / 25.0;
+                (-distance * distance).exp()
+            }
+        }
+    }
+}
+
+impl AgeShape {
+    /// Returns the weight of each year of age, starting at age 0.
+    pub fn weights(&self) -> Vec<f32> {
+        match self {
+            AgeShape::Uniform { max_age } => vec![1.0; *max_age as usize + 1],
+            AgeShape::ExponentialDecay { rate, max_age } => (0..=*max_age)
+                .map(|age| (-rate * age as f32).exp())
+                .collect(),
+            AgeShape::Pyramid(shape) => (0..=100).map(|age| shape.weight(age as f32)).collect(),
+            AgeShape::Weights(weights) => weights.clone(),
+        }
+    }
+
+    /// Creates a joint age/sex pdf, splitting each age by the given male fraction.
+    pub fn to_pdf(&self, male_fraction: f32) -> Result<AgePDF, Box<dyn std::error::Error>> {
+        AgePDF::from_weights(&self.weights(), male_fraction)
+    }
+}
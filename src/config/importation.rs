@@ -0,0 +1,99 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use vector::Vector2;
+
+use crate::CONFIG;
+
+use super::step_lookup;
+
+/// Where imported cases appear.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SpawnLocation {
+    Anywhere,
+    Point { position: (f32, f32), radius: f32 }, // E.g. an airport.
+    Border, // Within the infection radius of the edges of the simulated area.
+}
+
+/// How imported cases enter the simulation.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ImportationMode {
+    Infect,   // Infects a susceptible resident at the spawn location.
+    Traveler, // Adds an infected traveler to the population.
+}
+
+/// Cases imported from outside the simulated area.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Importation {
+    /// Expected imported cases per tick as (start tick, rate) steps, sorted by tick.
+    /// E.g. [(0, 0.01), (500, 0.0)] imports during the first 500 ticks only.
+    pub rate: Vec<(u32, f32)>,
+    pub location: SpawnLocation,
+    pub mode: ImportationMode,
+    #[serde(default)]
+    pub strain: Option<String>, // Name of the strain of the imported cases. The base strain if None.
+}
+
+impl Importation {
+    /// Returns the expected number of imported cases at the given time.
+    pub fn rate_at(&self, time: u32) -> f32 {
+        step_lookup(&self.rate, time)
+    }
+}
+
+impl SpawnLocation {
+    /// Returns true if the given position lies within the spawn location.
+    pub fn contains(&self, position: &Vector2<f32>) -> bool {
+        match self {
+            SpawnLocation::Anywhere => true,
+            SpawnLocation::Point {
+                position: center,
+                radius,
+            } => position.distance(&Vector2::new(center.0, center.1)) <= *radius,
+            SpawnLocation::Border => {
+                let band = CONFIG.core.infection_radius as f32;
+                let (width, height) = (
+                    CONFIG.core.dimensions.0 as f32,
+                    CONFIG.core.dimensions.1 as f32,
+                );
+
+                position.x < band
+                    || position.y < band
+                    || position.x >= width - band
+                    || position.y >= height - band
+            }
+        }
+    }
+
+    /// Samples a position within the spawn location.
+    pub fn sample(&self, rng: &mut StdRng) -> (f32, f32) {
+        let (width, height) = (
+            CONFIG.core.dimensions.0 as f32,
+            CONFIG.core.dimensions.1 as f32,
+        );
+
+        match self {
+            SpawnLocation::Anywhere => (rng.gen_range(0.0..width), rng.gen_range(0.0..height)),
+            SpawnLocation::Point { position, radius } => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let distance = radius * rng.gen::<f32>().sqrt();
+                (
+                    (position.0 + distance * angle.cos()).clamp(0.0, width - 1.0),
+                    (position.1 + distance * angle.sin()).clamp(0.0, height - 1.0),
+                )
+            }
+            SpawnLocation::Border => {
+                // A random point along the perimeter.
+                let along = rng.gen_range(0.0..2.0 * (width + height));
+                if along < width {
+                    (along, 0.0)
+                } else if along < width + height {
+                    (width - 1.0, along - width)
+                } else if along < 2.0 * width + height {
+                    (along - width - height, height - 1.0)
+                } else {
+                    (0.0, along - 2.0 * width - height)
+                }
+            }
+        }
+    }
+}
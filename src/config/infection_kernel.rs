@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Built-in chance of an infected entity infecting a susceptible one during a single tick,
+/// depending on their distance. Replaces the infection chance function if configured.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum InfectionKernel {
+    Constant(f32), // Same chance for every contact within the infection radius.
+    Exponential { chance: f32, scale: f32 }, // chance * e^(-distance / scale).
+    InverseSquare { chance: f32, cap: f32 }, // chance / distance², but at most cap.
+}
+
+impl InfectionKernel {
+    pub fn chance(&self, distance: f32) -> f32 {
+        match self {
+            InfectionKernel::Constant(chance) => *chance,
+            InfectionKernel::Exponential { chance, scale } => {
+                chance * (-distance / scale.max(f32::EPSILON)).exp()
+            }
+            InfectionKernel::InverseSquare { chance, cap } => {
+                (chance / (distance * distance)).min(*cap)
+            }
+        }
+    }
+}
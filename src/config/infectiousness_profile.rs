@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use super::{interpolate, validate_increasing};
+
+/// Relative infectiousness of an infected entity over the ticks since its infection.
+/// Scales the infection kernel, and is available to the infection chance function
+/// via Entity::infectiousness.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum InfectiousnessProfile {
+    #[default]
+    Flat, // Equally infectious during the whole infection.
+    /// Rises linearly from 0.0 at onset to 1.0 at peak, afterwards halves every half_life ticks.
+    Peak {
+        onset: u32,
+        peak: u32,
+        half_life: f32,
+    },
+    /// Gamma shaped, e.g. a measured generation interval. Scaled to a maximum of 1.0.
+    Gamma { mean: f32, shape: f32 },
+    /// Points of (ticks since infection, infectiousness), linearly interpolated in between.
+    /// Sorted by ticks, the first and last value are kept beyond them.
+    Table(Vec<(u32, f32)>),
+}
+
+impl InfectiousnessProfile {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            InfectiousnessProfile::Gamma { mean, shape } if *mean <= 0.0 || *shape <= 0.0 => {
+                Err(format!(
+                    "the gamma infectiousness profile needs a positive mean and shape, got {} and {}",
+                    mean, shape
+                )
+                .into())
+            }
+            InfectiousnessProfile::Table(points) => {
+                validate_increasing("ticks of the infectiousness profile", points)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn at(&self, time_since_infection: u32) -> f32 {
+        let t = time_since_infection as f32;
+
+        match self {
+            InfectiousnessProfile::Flat => 1.0,
+            InfectiousnessProfile::Peak {
+                onset,
+                peak,
+                half_life,
+            } => {
+                if time_since_infection < *onset {
+                    0.0
+                } else if time_since_infection < *peak {
+                    (t - *onset as f32) / (*peak - *onset) as f32
+                } else {
+                    0.5f32.powf((t - *peak as f32) / half_life.max(f32::EPSILON))
+                }
+            }
+            InfectiousnessProfile::Gamma { mean, shape } => {
+                let scale = mean / shape;
+                let mode = (shape - 1.0) * scale;
+
+                if mode <= 0.0 {
+                    // Monotonically decreasing for shapes up to 1.0.
+                    (-t / scale).exp()
+                } else {
+                    ((shape - 1.0) * (t / mode).ln() - (t - mode) / scale).exp()
+                }
+            }
+            InfectiousnessProfile::Table(points) => interpolate(points, time_since_infection),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Gamma};
+use serde::{Deserialize, Serialize};
+
+/// Distribution of the individual infectiousness of an entity, drawn anew on every infection.
+/// Multiplies the infectiousness profile, the mean is always 1.0.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum InfectiousnessVariation {
+    #[default]
+    Homogeneous, // Every entity is equally infectious.
+    /// Gamma distributed with shape k = dispersion, so that the number of secondary infections
+    /// follows a negative binomial distribution. Small values, e.g. 0.1, yield superspreaders.
+    Gamma { dispersion: f32 },
+}
+
+impl InfectiousnessVariation {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            InfectiousnessVariation::Gamma { dispersion }
+                if !dispersion.is_finite() || *dispersion <= 0.0 =>
+            {
+                Err(format!("the dispersion {} has to be positive", dispersion).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut StdRng) -> f32 {
+        match self {
+            InfectiousnessVariation::Homogeneous => 1.0,
+            InfectiousnessVariation::Gamma { dispersion } => {
+                Gamma::new(*dispersion, 1.0 / dispersion)
+                    .expect("The dispersion is validated with the config.")
+                    .sample(rng)
+            }
+        }
+    }
+}
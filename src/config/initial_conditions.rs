@@ -0,0 +1,143 @@
+use quadtree::Positioned;
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use vector::Vector2;
+
+use crate::{
+    entity::{Entity, EntityId, InfectionStatus},
+    CONFIG,
+};
+
+use super::{step_lookup, validate_increasing, Strain};
+
+/// How far the initial infections and immunity have run their course.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum InitialProgress {
+    #[default]
+    Start, // Infections just started, immunity is fresh.
+    Uniform,    // Uniformly distributed over the infected and recovered periods.
+    Fixed(f32), // Fraction of the infected and recovered periods that has passed, in [0, 1].
+}
+
+impl InitialProgress {
+    pub fn sample(&self, rng: &mut StdRng) -> f32 {
+        match self {
+            InitialProgress::Start => 0.0,
+            InitialProgress::Uniform => rng.gen(),
+            InitialProgress::Fixed(progress) => progress.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A group of infections seeded around a location, e.g. an observed outbreak.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    pub position: (f32, f32),
+    pub infected: u32, // The nearest susceptible entities to the position are infected.
+    #[serde(default)]
+    pub strain: Option<String>, // Name of the strain of the cluster. The base strain if None.
+}
+
+/// Exact initial state of the population, replacing the independent initial_infected chance.
+/// Applied in order: immunity by age, recovered, clusters, then the remaining infected.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InitialConditions {
+    #[serde(default)]
+    pub infected: u32, // Infected entities placed at random, besides the clusters.
+    #[serde(default)]
+    pub recovered: u32, // Recovered entities placed at random, besides the immunity by age.
+    #[serde(default)]
+    pub clusters: Vec<Cluster>,
+    /// Fraction of entities with pre-existing immunity as (minimum age, fraction) steps, sorted by age.
+    /// E.g. [(0, 0.0), (60, 0.3)] makes 30% of the entities aged 60 and above recovered.
+    #[serde(default)]
+    pub immunity: Vec<(u8, f32)>,
+    #[serde(default)]
+    pub progress: InitialProgress,
+}
+
+impl InitialConditions {
+    pub fn validate(
+        &self,
+        population_size: u32,
+        strains: &[Strain],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        validate_increasing("ages of the initial immunity", &self.immunity)?;
+
+        for cluster in &self.clusters {
+            if let Some(strain) = &cluster.strain {
+                if !strains.iter().any(|configured| &configured.name == strain) {
+                    return Err(format!("cluster strain {} is not configured", strain).into());
+                }
+            }
+        }
+
+        let seeded = self.infected
+            + self.recovered
+            + self
+                .clusters
+                .iter()
+                .map(|cluster| cluster.infected)
+                .sum::<u32>();
+        if seeded > population_size {
+            return Err(format!(
+                "the initial conditions seed {} entities, but the population size is {}",
+                seeded, population_size
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fraction of entities of the given age with pre-existing immunity.
+    fn immunity(&self, age: u8) -> f32 {
+        step_lookup(&self.immunity, age)
+    }
+
+    /// Sets the initial state of the given, entirely susceptible population.
+    pub fn apply(&self, population: &mut [Entity], rng: &mut StdRng) {
+        for entity in population.iter_mut() {
+            if rng.gen::<f32>() < self.immunity(entity.age()) {
+                entity.seed_immunity(self.progress.sample(rng));
+            }
+        }
+
+        for entity_id in Self::susceptible(population).choose_multiple(rng, self.recovered as usize)
+        {
+            population[*entity_id].seed_immunity(self.progress.sample(rng));
+        }
+
+        for cluster in &self.clusters {
+            let position = Vector2::new(cluster.position.0, cluster.position.1);
+            let strain = cluster
+                .strain
+                .as_ref()
+                .and_then(|name| CONFIG.strain_index(name));
+
+            let mut nearest = Self::susceptible(population);
+            nearest.sort_by(|a, b| {
+                let distance_a = population[*a].position().distance(&position);
+                let distance_b = population[*b].position().distance(&position);
+                distance_a.total_cmp(&distance_b)
+            });
+
+            for entity_id in nearest.iter().take(cluster.infected as usize) {
+                population[*entity_id].seed_infection(strain, self.progress.sample(rng));
+            }
+        }
+
+        for entity_id in Self::susceptible(population).choose_multiple(rng, self.infected as usize)
+        {
+            population[*entity_id].seed_infection(None, self.progress.sample(rng));
+        }
+    }
+
+    fn susceptible(population: &[Entity]) -> Vec<EntityId> {
+        population
+            .iter()
+            .filter(|entity| *entity.status() == InfectionStatus::Susceptible)
+            .map(|entity| entity.id())
+            .collect()
+    }
+}
@@ -2,5 +2,29 @@ mod age_distribution;
 mod age_pdf;
 mod config;
 mod core;
+mod demographic;
+mod importation;
+mod infection_kernel;
+mod infectiousness_profile;
+mod infectiousness_variation;
+mod initial_conditions;
+mod population_file;
+mod risk_factor;
+mod seasonality;
+mod strain;
+mod table;
+mod vitals;
 
 pub use config::Config;
+pub use demographic::Demographic;
+pub use importation::{Importation, ImportationMode};
+pub use infection_kernel::InfectionKernel;
+pub use infectiousness_profile::InfectiousnessProfile;
+pub use infectiousness_variation::InfectiousnessVariation;
+pub use initial_conditions::InitialConditions;
+pub use population_file::{PopulationEntry, PopulationFile};
+pub use risk_factor::RiskFactor;
+pub use seasonality::Seasonality;
+pub use strain::Strain;
+pub use table::{interpolate, step_lookup, validate_increasing};
+pub use vitals::Vitals;
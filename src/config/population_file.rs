@@ -0,0 +1,135 @@
+use std::{fs, io::Write};
+
+use crate::{
+    entity::{Entity, Sex},
+    CONFIG,
+};
+
+/// Everything describing an entity before the simulation starts,
+/// except for its infection status.
+pub struct PopulationEntry {
+    pub age: u8,
+    pub sex: Sex,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    pub mobile: bool,
+}
+
+/// A population file stores a generated population as csv,
+/// so later runs can reuse the exact same population.
+pub struct PopulationFile;
+
+const HEADER: &str = "age,sex,x,y,vx,vy,mobile";
+
+impl PopulationFile {
+    /// Loads the population entries from the given csv file.
+    /// Positions have to lie within the simulation space of the given dimensions.
+    pub fn load(
+        path: &str,
+        dimensions: (u32, u32),
+    ) -> Result<Vec<PopulationEntry>, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        let mut entries = Vec::new();
+
+        let header = contents.lines().next().unwrap_or("").trim();
+        if header != HEADER {
+            return Err(format!(
+                "{}: line 1: expected the header '{}', found '{}'",
+                path, HEADER, header
+            )
+            .into());
+        }
+
+        for (index, line) in contents.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = PopulationFile::parse_entry(line, dimensions)
+                .map_err(|message| format!("{}: line {}: {}", path, index + 1, message))?;
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    fn parse_entry(line: &str, dimensions: (u32, u32)) -> Result<PopulationEntry, String> {
+        let parts: Vec<&str> = line.split(',').map(|part| part.trim()).collect();
+        if parts.len() != 7 {
+            return Err(format!("expected 7 columns, found {}", parts.len()));
+        }
+
+        let parse_f32 = |value: &str| {
+            value
+                .parse::<f32>()
+                .map_err(|_| format!("invalid number '{}'", value))
+        };
+
+        let age = parts[0]
+            .parse::<u8>()
+            .map_err(|_| format!("invalid age '{}'", parts[0]))?;
+        let sex = Sex::parse(parts[1]).ok_or_else(|| format!("invalid sex '{}'", parts[1]))?;
+        let position = (parse_f32(parts[2])?, parse_f32(parts[3])?);
+        let velocity = (parse_f32(parts[4])?, parse_f32(parts[5])?);
+        let mobile = parts[6]
+            .parse::<bool>()
+            .map_err(|_| format!("invalid mobility '{}'", parts[6]))?;
+
+        if !(0.0..dimensions.0 as f32).contains(&position.0)
+            || !(0.0..dimensions.1 as f32).contains(&position.1)
+        {
+            return Err(format!(
+                "position ({}, {}) is outside of the simulation space",
+                position.0, position.1
+            ));
+        }
+
+        Ok(PopulationEntry {
+            age,
+            sex,
+            position,
+            velocity,
+            mobile,
+        })
+    }
+
+    /// Writes the given population to a population file.
+    pub fn export(population: &[Entity], path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut csv = String::new();
+        csv.push_str(HEADER);
+        csv.push('\n');
+
+        for entity in population {
+            let entry = entity.to_entry();
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                entry.age,
+                entry.sex.as_str(),
+                entry.position.0,
+                entry.position.1,
+                entry.velocity.0,
+                entry.velocity.1,
+                entry.mobile
+            ));
+        }
+
+        let mut file = fs::File::create(path)?;
+        file.write_all(csv.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Writes the given population to the export folder of the current run,
+    /// if export_population is enabled.
+    pub fn export_run(population: &[Entity]) -> Result<(), Box<dyn std::error::Error>> {
+        if !CONFIG.core.export_population {
+            return Ok(());
+        }
+
+        fs::create_dir_all(format!("export/{}", CONFIG.name()))?;
+        PopulationFile::export(
+            population,
+            &format!("export/{}/population.csv", CONFIG.name()),
+        )
+    }
+}
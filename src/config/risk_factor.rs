@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use super::step_lookup;
+
+/// A pre-existing condition, e.g. a comorbidity, present from the start of an entitys life.
+/// Whether an entity has it is sampled once, given the age of the entity.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RiskFactor {
+    pub name: String,
+    /// Fraction of entities having the risk factor as (minimum age, fraction) steps, sorted by age.
+    /// E.g. [(0, 0.01), (40, 0.08), (65, 0.2)].
+    pub prevalence: Vec<(u8, f32)>,
+}
+
+impl RiskFactor {
+    /// Returns the fraction of entities of the given age having the risk factor.
+    pub fn prevalence(&self, age: u8) -> f32 {
+        step_lookup(&self.prevalence, age)
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Seasonal forcing of transmission, a multiplier on the chance of infection depending on the date.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Seasonality {
+    /// 1 + amplitude * cos(2π (day of year - peak_day) / 365), highest at peak_day.
+    Sinusoidal { amplitude: f32, peak_day: u32 },
+    /// One multiplier per calendar month, starting with January.
+    Monthly(Vec<f32>),
+}
+
+impl Seasonality {
+    pub fn factor(&self, date: NaiveDate) -> f32 {
+        match self {
+            Seasonality::Sinusoidal {
+                amplitude,
+                peak_day,
+            } => {
+                let phase = (date.ordinal() as f32 - *peak_day as f32) / 365.0;
+                (1.0 + amplitude * (2.0 * std::f32::consts::PI * phase).cos()).max(0.0)
+            }
+            Seasonality::Monthly(multipliers) => multipliers[date.month0() as usize],
+        }
+    }
+
+    /// Checks the multipliers cover every month.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Seasonality::Monthly(multipliers) if multipliers.len() != 12 => Err(format!(
+                "monthly seasonality needs 12 multipliers, got {}",
+                multipliers.len()
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// A variant of the pathogen. Infections pass their strain on to the entities they infect.
+/// Infections without a strain, e.g. those present at the start, are of the base strain.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Strain {
+    pub name: String,
+    pub transmissibility: f32, // Multiplies the infectiousness relative to the base strain.
+}
@@ -0,0 +1,76 @@
+/// Returns the value of a step function given as (start, value) steps sorted by start,
+/// i.e. the value of the last step starting at or before x, or 0 before the first step.
+pub fn step_lookup<T: PartialOrd>(table: &[(T, f32)], x: T) -> f32 {
+    let started = table.partition_point(|(start, _)| *start <= x);
+    started.checked_sub(1).map_or(0.0, |last| table[last].1)
+}
+
+/// Linearly interpolates between (x, y) points sorted by x.
+/// The first and last value are kept beyond them, an empty table is 0.
+pub fn interpolate<T: PartialOrd + Copy + Into<f64>>(table: &[(T, f32)], x: T) -> f32 {
+    let next = table.partition_point(|(at, _)| *at <= x);
+
+    match (
+        next.checked_sub(1).map(|last| table[last]),
+        table.get(next).copied(),
+    ) {
+        (Some((x0, y0)), Some((x1, y1))) => {
+            let fraction = (x.into() - x0.into()) / (x1.into() - x0.into());
+            y0 + (y1 - y0) * fraction as f32
+        }
+        (Some((_, y)), None) | (None, Some((_, y))) => y,
+        (None, None) => 0.0,
+    }
+}
+
+/// Checks that the keys of a table are strictly increasing, which the lookups rely on.
+pub fn validate_increasing<T: PartialOrd + std::fmt::Display>(
+    name: &str,
+    table: &[(T, f32)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for pair in table.windows(2) {
+        if pair[1].0 <= pair[0].0 {
+            return Err(format!(
+                "the {} have to be strictly increasing, but {} follows {}",
+                name, pair[1].0, pair[0].0
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_steps() {
+        let table = [(0u8, 0.1), (40, 0.2), (65, 0.3)];
+
+        assert_eq!(step_lookup(&table, 0), 0.1);
+        assert_eq!(step_lookup(&table, 39), 0.1);
+        assert_eq!(step_lookup(&table, 40), 0.2);
+        assert_eq!(step_lookup(&table, 100), 0.3);
+        assert_eq!(step_lookup(&[(10u32, 0.5)], 5), 0.0);
+    }
+
+    #[test]
+    fn interpolates_between_points() {
+        let table = [(0.2f32, 0.0), (0.6, 1.0)];
+
+        assert_eq!(interpolate(&table, 0.0), 0.0);
+        assert!((interpolate(&table, 0.3) - 0.25).abs() < 1e-6);
+        assert_eq!(interpolate(&table, 0.6), 1.0);
+        assert_eq!(interpolate(&table, 1.0), 1.0);
+        assert_eq!(interpolate::<u32>(&[], 3), 0.0);
+    }
+
+    #[test]
+    fn rejects_unsorted_keys() {
+        assert!(validate_increasing("ages", &[(0u8, 0.1), (40, 0.2)]).is_ok());
+        assert!(validate_increasing("ages", &[(40u8, 0.1), (40, 0.2)]).is_err());
+        assert!(validate_increasing("ages", &[(65u8, 0.1), (40, 0.2)]).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+use super::step_lookup;
+
+/// Births, natural deaths and ageing for simulations spanning several years.
+/// Entities age by a year on their birthday, die of natural causes
+/// with an age-specific chance, and susceptible newborns join the population.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Vitals {
+    pub birth_rate: f32,    // Births per 1000 living entities and year.
+    pub male_fraction: f32, // Fraction of male newborns.
+    /// Annual chance of dying of natural causes as (minimum age, chance) steps, sorted by age.
+    /// E.g. [(0, 0.004), (1, 0.0002), (40, 0.002), (65, 0.015), (85, 0.12)].
+    pub mortality: Vec<(u8, f32)>,
+}
+
+impl Vitals {
+    /// Returns the annual chance of dying of natural causes at the given age.
+    pub fn mortality(&self, age: u8) -> f32 {
+        step_lookup(&self.mortality, age)
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{compartmental::GillespieConfig, statistics::DataFrame};
+
+/// Common interface of the simulation engines, advancing a population tick by tick.
+pub trait Engine {
+    fn step(&mut self);
+    fn done(&self) -> bool;
+    fn current_time(&self) -> u32;
+    /// Records the state after the current tick.
+    fn push_data(&self, dataframe: &mut DataFrame);
+}
+
+/// Engine used to run the simulation.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum EngineKind {
+    #[default]
+    Spatial, // Agent-based simulation of moving entities, see Simulator.
+    Gillespie(GillespieConfig), // Stochastic compartmental model on a well-mixed or network population. Runs without graphics.
+}
@@ -1,8 +1,16 @@
 use quadtree::Positioned;
 use rand::{prelude::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use vector::Vector2;
 
-use crate::CONFIG;
+use crate::{
+    config::{PopulationEntry, Vitals},
+    hospital::CareTier,
+    interventions::Lockdown,
+    movement::MovementModel,
+    testing::TestKind,
+    CONFIG,
+};
 
 #[derive(PartialEq)]
 pub enum InfectionStatus {
@@ -12,17 +20,81 @@ pub enum InfectionStatus {
     Dead,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeathCause {
+    Disease,
+    CareNotAvailable, // Severe case that could not be admitted because the hospital was full.
+    Natural,          // Background mortality, unrelated to the disease.
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl Sex {
+    pub fn parse(sex: &str) -> Option<Sex> {
+        match sex {
+            "male" => Some(Sex::Male),
+            "female" => Some(Sex::Female),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        }
+    }
+}
+
+/// Role of an entity besides being part of the population.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Occupation {
+    General,
+    HealthCareWorker, // Staff of a hospital, see NosocomialConfig.
+}
+
+impl Occupation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Occupation::General => "general",
+            Occupation::HealthCareWorker => "health_care_worker",
+        }
+    }
+}
+
+/// Stable identifier of an entity.
+/// Equals the entitys index in the population, which never reorders or removes entities.
+pub type EntityId = usize;
+
 pub struct Entity {
+    id: EntityId,
+
     position: Vector2<f32>, // Used for calculating entity movement.
     velocity: Vector2<f32>,
     acceleration: Vector2<f32>,
 
     health: InfectionStatus,
 
-    hospitalized: bool,
+    care: Option<CareTier>,     // Tier of care if the entity is hospitalized.
+    symptomatic: bool, // Whether the current infection shows symptoms. Sampled on infection.
+    infection_risk: f32, // Chance of dying of the current infection without care. Set on infection.
+    infectiousness_factor: f32, // Individual infectiousness of the current infection. Set on infection.
+    strain: Option<usize>,      // Strain of the current infection, None for the base strain.
+    care_denied: bool, // Whether the entity was denied care during the current infection due to a full hospital.
+    death_cause: Option<DeathCause>,
     mobile: bool, // True if the entity can move (Neither dead, nor in Hospital). False if it is immobile.
 
     age: u8,
+    birthday: u32, // Tick within the year at which the entity ages.
+    sex: Sex,
+    occupation: Occupation,
+    risk_factors: u32, // Bit set of the configured risk factors the entity has.
+    flight: f32,       // Remaining length of the current flight along the velocity, see Levy.
+    compliance: f32, // Propensity to follow interventions. Entities below an interventions compliance follow it.
 
     rng: StdRng,
 }
@@ -35,49 +107,159 @@ impl Positioned for Entity {
 }
 
 impl Entity {
-    pub fn new() -> Entity {
+    pub fn new(id: EntityId) -> Entity {
         let mut rng: StdRng = rand::rngs::StdRng::from_entropy();
 
+        let (age, sex) = CONFIG.sample_demographic(&mut rng);
+        let entry = Entity::sample_entry(age, sex, &mut rng);
+
+        Entity::from_entry_with_rng(id, &entry, rng)
+    }
+
+    /// Creates a susceptible newborn at a random position, born at the given time.
+    pub fn newborn(id: EntityId, vitals: &Vitals, time: u32) -> Entity {
+        let mut rng: StdRng = rand::rngs::StdRng::from_entropy();
+
+        let sex = if rng.gen::<f32>() < vitals.male_fraction {
+            Sex::Male
+        } else {
+            Sex::Female
+        };
+        let entry = Entity::sample_entry(0, sex, &mut rng);
+
+        Entity::with_entry(id, &entry, time % CONFIG.ticks_per_year(), rng)
+    }
+
+    /// Creates an infected traveler arriving at the given position.
+    pub fn traveler(
+        id: EntityId,
+        age: u8,
+        sex: Sex,
+        position: (f32, f32),
+        strain: Option<usize>,
+    ) -> Entity {
+        let mut rng: StdRng = rand::rngs::StdRng::from_entropy();
+
+        let mut entry = Entity::sample_entry(age, sex, &mut rng);
+        entry.position = position;
+
+        let mut entity = Entity::from_entry_with_rng(id, &entry, rng);
+        entity.infect(strain);
+        entity
+    }
+
+    /// Samples the position, velocity and mobility of an entity of the given age and sex.
+    fn sample_entry(age: u8, sex: Sex, rng: &mut StdRng) -> PopulationEntry {
         let x_position = rng.gen_range(0.0..CONFIG.core.dimensions.0 as f32);
         let y_position = rng.gen_range(0.0..CONFIG.core.dimensions.1 as f32);
 
-        let chance = rng.gen::<f32>();
-        let infected = chance < CONFIG.core.initial_infected;
-
         let chance = rng.gen::<f32>();
         let mobile = chance < CONFIG.core.initial_mobile;
 
-        let age = CONFIG.sample_age(&mut rng);
-
         let speed_range = (-CONFIG.core.max_velocity * 0.1)..(CONFIG.core.max_velocity * 0.1);
+        let velocity = (
+            rng.gen_range(speed_range.clone()),
+            rng.gen_range(speed_range),
+        );
+
+        PopulationEntry {
+            age,
+            sex,
+            position: (x_position, y_position),
+            velocity,
+            mobile,
+        }
+    }
+
+    /// Creates an entity from an entry of a population file.
+    /// Only the infection status is sampled.
+    pub fn from_entry(id: EntityId, entry: &PopulationEntry) -> Entity {
+        Entity::from_entry_with_rng(id, entry, rand::rngs::StdRng::from_entropy())
+    }
+
+    fn from_entry_with_rng(id: EntityId, entry: &PopulationEntry, mut rng: StdRng) -> Entity {
+        let birthday = rng.gen_range(0..CONFIG.ticks_per_year());
+        let mut entity = Entity::with_entry(id, entry, birthday, rng);
 
+        // The initial conditions infect the population once it is complete.
+        if CONFIG.core.initial_conditions.is_none() && entity.rand() < CONFIG.core.initial_infected
+        {
+            entity.infect(None);
+        }
+
+        entity
+    }
+
+    /// Creates a susceptible entity with the given birthday, a tick within the year.
+    fn with_entry(id: EntityId, entry: &PopulationEntry, birthday: u32, mut rng: StdRng) -> Entity {
         Entity {
-            position: Vector2::new(x_position, y_position),
-            velocity: Vector2::new(
-                rng.gen_range(speed_range.clone()),
-                rng.gen_range(speed_range),
-            ),
+            id,
+            position: Vector2::new(entry.position.0, entry.position.1),
+            velocity: Vector2::new(entry.velocity.0, entry.velocity.1),
             acceleration: Vector2::new(0.0, 0.0),
-            health: if infected {
-                InfectionStatus::Infected(CONFIG.core.infected_period)
-            } else {
-                InfectionStatus::Susceptible
-            },
-            hospitalized: false,
-            mobile,
-            age,
+            health: InfectionStatus::Susceptible,
+            care: None,
+            symptomatic: false,
+            infection_risk: 0.0,
+            infectiousness_factor: 1.0,
+            strain: None,
+            care_denied: false,
+            death_cause: None,
+            mobile: entry.mobile,
+            age: entry.age,
+            birthday,
+            sex: entry.sex,
+            occupation: Occupation::General,
+            risk_factors: Entity::sample_risk_factors(entry.age, &mut rng),
+            flight: 0.0,
+            compliance: rng.gen::<f32>(),
             rng,
         }
     }
 
-    /// Simple model for force based movement.
-    /// Maximum velocity is limited to CONFIG.core.max_velocity.
-    pub fn update_movement(&mut self) {
+    /// Samples the configured risk factors given the age of the entity.
+    fn sample_risk_factors(age: u8, rng: &mut StdRng) -> u32 {
+        CONFIG
+            .core
+            .risk_factors
+            .iter()
+            .enumerate()
+            .filter(|(_, risk_factor)| rng.gen::<f32>() < risk_factor.prevalence(age))
+            .fold(0, |risk_factors, (index, _)| risk_factors | (1 << index))
+    }
+
+    /// Describes this entity as an entry of a population file.
+    pub fn to_entry(&self) -> PopulationEntry {
+        PopulationEntry {
+            age: self.age,
+            sex: self.sex,
+            position: (self.position.x, self.position.y),
+            velocity: (self.velocity.x, self.velocity.y),
+            mobile: self.mobile,
+        }
+    }
+
+    /// Moves the entity by a single tick using the given movement model.
+    /// Maximum velocity is limited to CONFIG.core.max_velocity, or that of the lockdown in effect.
+    /// Entities complying with the lockdown do not move at all.
+    pub fn update_movement(&mut self, model: &dyn MovementModel, lockdown: Option<&Lockdown>) {
         if !self.mobile {
             return;
         }
 
-        self.velocity.clamp_mag(CONFIG.core.max_velocity);
+        let max_velocity = match lockdown {
+            Some(lockdown) if self.complies(1.0 - lockdown.mobile) => return,
+            Some(lockdown) => lockdown.max_velocity.min(CONFIG.core.max_velocity),
+            None => CONFIG.core.max_velocity,
+        };
+
+        model.step(self, max_velocity);
+    }
+
+    /// Simple model for force based movement.
+    /// Applies the accumulated forces and moves the entity at its velocity.
+    pub fn integrate(&mut self, max_velocity: f32) {
+        self.velocity.clamp_mag(max_velocity);
         self.position += self.velocity;
         self.velocity += self.acceleration;
         self.acceleration *= 0.0;
@@ -85,6 +267,51 @@ impl Entity {
         self.check_boundaries();
     }
 
+    /// Starts a flight of the given length in the direction of the given unit vector.
+    pub fn start_flight(&mut self, direction: Vector2<f32>, length: f32) {
+        self.velocity = direction;
+        self.flight = length;
+    }
+
+    /// Returns the remaining length of the current flight.
+    pub fn flight(&self) -> f32 {
+        self.flight
+    }
+
+    /// Continues the current flight by at most the given velocity.
+    /// Flights end early at the boundaries.
+    pub fn continue_flight(&mut self, max_velocity: f32) {
+        let step = self.flight.min(max_velocity);
+        self.flight -= step;
+
+        if self.displace(self.velocity * step) {
+            self.flight = 0.0;
+        }
+    }
+
+    /// Moves the entity by the given offset, reflected at the boundaries.
+    /// Returns true if the entity was reflected.
+    pub fn displace(&mut self, offset: Vector2<f32>) -> bool {
+        let reflect = |value: f32, size: f32| {
+            let value = if value < 0.0 {
+                -value
+            } else if value >= size {
+                2.0 * size - value
+            } else {
+                value
+            };
+
+            // Jumps longer than the simulation space are reflected only once.
+            value.clamp(0.0, size - 1.0)
+        };
+
+        let target = self.position + offset;
+        self.position.x = reflect(target.x, CONFIG.core.dimensions.0 as f32);
+        self.position.y = reflect(target.y, CONFIG.core.dimensions.1 as f32);
+
+        self.position.x != target.x || self.position.y != target.y
+    }
+
     /// Check if the entity is outside the boundaries.
     /// Reverse the velocity if it is.
     fn check_boundaries(&mut self) {
@@ -101,22 +328,19 @@ impl Entity {
         }
     }
 
-    /// Run an infection test on this entity.
-    pub fn test(&mut self) -> bool {
+    /// Run an infection test of the given kind on this entity.
+    /// The chance of detecting an infection depends on its progress.
+    pub fn test(&mut self, kind: &TestKind) -> bool {
         match self.health {
-            InfectionStatus::Susceptible => {
-                let rng = self.rand();
-                !(rng < CONFIG.core.test_true_negative)
-            }
             InfectionStatus::Infected(_) => {
                 let rng = self.rand();
-                rng < CONFIG.core.test_true_positive
+                rng < kind.sensitivity.at(self.infection_progress())
             }
-            InfectionStatus::Recovered(_) => {
+            InfectionStatus::Dead => false,
+            _ => {
                 let rng = self.rand();
-                !(rng < CONFIG.core.test_true_negative)
+                rng >= kind.specificity
             }
-            InfectionStatus::Dead => false,
         }
     }
 
@@ -131,28 +355,148 @@ impl Entity {
 
     pub fn recover(&mut self) {
         self.health = InfectionStatus::Recovered(CONFIG.core.recovered_period);
+        self.care_denied = false;
+    }
+
+    /// Infects the entity with an infection that has already run the given fraction of its course.
+    pub fn seed_infection(&mut self, strain: Option<usize>, progress: f32) {
+        self.infect(strain);
+        let elapsed = (CONFIG.core.infected_period as f32 * progress) as u32;
+        self.health = InfectionStatus::Infected(CONFIG.core.infected_period - elapsed);
+    }
+
+    /// Makes the entity recovered with immunity that has already run the given fraction of its course.
+    pub fn seed_immunity(&mut self, progress: f32) {
+        let elapsed = (CONFIG.core.recovered_period as f32 * progress) as u32;
+        self.health = InfectionStatus::Recovered(CONFIG.core.recovered_period - elapsed);
     }
 
-    pub fn die(&mut self) {
+    pub fn die(&mut self, cause: DeathCause) {
         self.health = InfectionStatus::Dead;
+        self.death_cause = Some(cause);
+    }
+
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        self.death_cause
+    }
+
+    /// Marks a severe case as denied care because the hospital is full.
+    /// Only has an effect if overflow mortality is configured.
+    pub fn deny_care(&mut self) {
+        if let Some(overflow) = &CONFIG.core.overflow_mortality {
+            if self.severity() >= overflow.min_severity {
+                self.care_denied = true;
+            }
+        }
     }
 
     pub fn is_hospitalized(&self) -> bool {
-        self.hospitalized
+        self.care.is_some()
     }
 
-    pub fn set_hospitalized(&mut self) {
-        self.hospitalized = true;
+    pub fn care_tier(&self) -> Option<CareTier> {
+        self.care
+    }
+
+    pub fn set_hospitalized(&mut self, tier: CareTier) {
+        self.care = Some(tier);
+        self.care_denied = false;
         self.mobile = false;
     }
 
     pub fn unset_hospitalized(&mut self) {
-        self.hospitalized = false;
+        self.care = None;
         self.mobile = true;
     }
 
-    pub fn infect(&mut self) {
+    /// Infects the entity with the given strain, None for the base strain.
+    pub fn infect(&mut self, strain: Option<usize>) {
+        self.strain = strain;
         self.health = InfectionStatus::Infected(CONFIG.core.infected_period);
+        self.symptomatic = self.rand() < CONFIG.core.symptomatic_chance;
+        self.infection_risk = self.risk_score();
+        self.infectiousness_factor = CONFIG.core.infectiousness_variation.sample(&mut self.rng);
+    }
+
+    /// Returns true if the entity is infected and shows symptoms.
+    pub fn is_symptomatic(&self) -> bool {
+        matches!(self.health, InfectionStatus::Infected(_)) && self.symptomatic
+    }
+
+    /// Chance of the entity dying if it was infected, given its current care.
+    pub fn risk_score(&self) -> f32 {
+        1.0 - self.survival_chance()
+    }
+
+    /// Chance of surviving the infection given the current care.
+    /// Intensive care tiers scale the chance of dying by their risk factor.
+    pub fn survival_chance(&self) -> f32 {
+        let survival_chance = (CONFIG.survival_chance)(self);
+
+        match self.care.and_then(|tier| tier.config()) {
+            Some(config) => 1.0 - (1.0 - survival_chance) * config.risk_factor,
+            None => survival_chance,
+        }
+    }
+
+    /// Additional chance of dying for severe cases without care.
+    fn overflow_mortality(&self) -> f32 {
+        CONFIG
+            .core
+            .overflow_mortality
+            .as_ref()
+            .map(|overflow| overflow.mortality)
+            .unwrap_or(0.0)
+    }
+
+    /// Current severity of the infection.
+    /// The chance of dying without care, scaled by the course of the infection
+    /// which rises linearly to its peak halfway through the infection and declines afterwards.
+    pub fn severity(&self) -> f32 {
+        match self.health {
+            InfectionStatus::Infected(_) => {
+                let progress = self.infection_progress();
+                self.infection_risk * (1.0 - (2.0 * progress - 1.0).abs())
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Ticks since the entity was infected, None if it is not infected.
+    pub fn time_since_infection(&self) -> Option<u32> {
+        match self.health {
+            InfectionStatus::Infected(time_remaining) => {
+                Some(CONFIG.core.infected_period.saturating_sub(time_remaining))
+            }
+            _ => None,
+        }
+    }
+
+    /// Relative infectiousness given the time since infection, see InfectiousnessProfile,
+    /// scaled by the individual infectiousness and the transmissibility of the strain.
+    /// 0.0 if the entity is not infected.
+    pub fn infectiousness(&self) -> f32 {
+        self.time_since_infection().map_or(0.0, |time| {
+            CONFIG.core.infectiousness.at(time)
+                * self.infectiousness_factor
+                * CONFIG.transmissibility(self.strain)
+        })
+    }
+
+    /// Returns the strain of the current or latest infection, None for the base strain.
+    pub fn strain(&self) -> Option<usize> {
+        self.strain
+    }
+
+    /// Progress of the current infection, from 0.0 when infected to 1.0 at its end.
+    /// 0.0 if the entity is not infected.
+    pub fn infection_progress(&self) -> f32 {
+        match self.health {
+            InfectionStatus::Infected(time_remaining) => {
+                1.0 - time_remaining as f32 / CONFIG.core.infected_period.max(1) as f32
+            }
+            _ => 0.0,
+        }
     }
 
     pub fn status(&self) -> &InfectionStatus {
@@ -163,10 +507,48 @@ impl Entity {
         self.health == InfectionStatus::Dead
     }
 
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
     pub fn age(&self) -> u8 {
         self.age
     }
 
+    pub fn sex(&self) -> Sex {
+        self.sex
+    }
+
+    pub fn occupation(&self) -> Occupation {
+        self.occupation
+    }
+
+    /// Returns true if the entity has the configured risk factor of the given name.
+    pub fn has_risk_factor(&self, name: &str) -> bool {
+        CONFIG
+            .risk_factor_index(name)
+            .is_some_and(|index| self.has_risk_factor_at(index))
+    }
+
+    /// Returns true if the entity has the risk factor at the given index of the configuration.
+    pub fn has_risk_factor_at(&self, index: usize) -> bool {
+        self.risk_factors & (1 << index) != 0
+    }
+
+    /// Returns the number of risk factors the entity has.
+    pub fn risk_factor_count(&self) -> u32 {
+        self.risk_factors.count_ones()
+    }
+
+    pub fn set_occupation(&mut self, occupation: Occupation) {
+        self.occupation = occupation;
+    }
+
+    /// Returns true if the entity follows an intervention with the given compliance.
+    pub fn complies(&self, compliance: f32) -> bool {
+        self.compliance < compliance
+    }
+
     pub fn health(&self) -> &InfectionStatus {
         &self.health
     }
@@ -175,6 +557,31 @@ impl Entity {
         self.rng.gen::<f32>()
     }
 
+    /// Returns the random number generator of the entity, e.g. to sample distributions.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Ages the entity on its birthday and lets it die of natural causes
+    /// with the per tick share of its annual mortality.
+    pub fn update_vitals(&mut self, vitals: &Vitals, time: u32) {
+        // Entities that died of the disease this tick keep their cause of death.
+        if self.is_dead() {
+            return;
+        }
+
+        let ticks_per_year = CONFIG.ticks_per_year();
+
+        if time % ticks_per_year == self.birthday {
+            self.age = self.age.saturating_add(1);
+        }
+
+        let mortality = 1.0 - (1.0 - vitals.mortality(self.age)).powf(1.0 / ticks_per_year as f32);
+        if self.rand() < mortality {
+            self.die(DeathCause::Natural);
+        }
+    }
+
     /// Performs the transition between
     /// the existing epidemic model groups.
     pub fn update_status(&mut self) {
@@ -183,10 +590,12 @@ impl Entity {
                 if time_remaining <= 0 {
                     let chance = self.rand();
 
-                    if chance <= (CONFIG.survival_chance)(self) {
-                        self.recover();
+                    if chance > self.survival_chance() {
+                        self.die(DeathCause::Disease);
+                    } else if self.care_denied && self.rand() < self.overflow_mortality() {
+                        self.die(DeathCause::CareNotAvailable);
                     } else {
-                        self.die();
+                        self.recover();
                     }
                 } else {
                     self.health = InfectionStatus::Infected(time_remaining - 1);
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{Entity, InfectionStatus};
+
+/// Determines who is eligible for admission after testing positive.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AdmissionCriterion {
+    Any,                      // Everyone testing positive, including false positives.
+    Symptomatic,              // Only infected entities showing symptoms.
+    Severe { min_risk: f32 }, // Only infected entities with a risk score of at least min_risk.
+}
+
+/// Determines the order in which eligible entities are admitted.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AdmissionPriority {
+    FirstComeFirstServed,
+    Age,  // Older entities first.
+    Risk, // Entities with a higher risk score first.
+}
+
+/// Triage strategy of the hospital.
+/// With preemption enabled, a full hospital releases its lowest priority patient
+/// if a new entity of higher priority arrives.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdmissionPolicy {
+    pub criterion: AdmissionCriterion,
+    pub priority: AdmissionPriority,
+    pub preemption: bool,
+}
+
+impl Default for AdmissionPolicy {
+    fn default() -> Self {
+        AdmissionPolicy {
+            criterion: AdmissionCriterion::Any,
+            priority: AdmissionPriority::FirstComeFirstServed,
+            preemption: false,
+        }
+    }
+}
+
+impl AdmissionPolicy {
+    /// Returns true if the entity may be admitted under this policy.
+    pub fn is_eligible(&self, entity: &Entity) -> bool {
+        let infected = matches!(entity.status(), InfectionStatus::Infected(_));
+
+        match self.criterion {
+            AdmissionCriterion::Any => true,
+            AdmissionCriterion::Symptomatic => infected && entity.is_symptomatic(),
+            AdmissionCriterion::Severe { min_risk } => infected && entity.risk_score() >= min_risk,
+        }
+    }
+
+    /// Returns the admission priority of the entity, higher values are admitted first.
+    pub fn priority(&self, entity: &Entity) -> f32 {
+        match self.priority {
+            AdmissionPriority::FirstComeFirstServed => 0.0,
+            AdmissionPriority::Age => entity.age() as f32,
+            AdmissionPriority::Risk => entity.risk_score(),
+        }
+    }
+}
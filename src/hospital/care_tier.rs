@@ -0,0 +1,70 @@
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Gamma};
+use serde::{Deserialize, Serialize};
+
+use crate::CONFIG;
+
+/// Levels of care a patient can receive, ordered from least to most intensive.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum CareTier {
+    Ward,
+    Icu,
+    Ventilator,
+}
+
+/// Distribution of the number of ticks a patient stays in a tier.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum LengthOfStay {
+    Fixed(u32),
+    Uniform { min: u32, max: u32 },
+    Gamma { mean: f32, shape: f32 }, // Right skewed stays, as commonly observed.
+}
+
+/// Parameters of the ICU and ventilator tiers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CareTierConfig {
+    pub capacity: u32,
+    pub length_of_stay: LengthOfStay, // Minimum stay before stepping down.
+    pub min_severity: f32,            // Severity at which a patient requires this tier.
+    pub risk_factor: f32,             // Multiplies the chance of dying, e.g. 0.5 halves it.
+}
+
+impl LengthOfStay {
+    pub fn sample(&self, rng: &mut StdRng) -> u32 {
+        match self {
+            LengthOfStay::Fixed(ticks) => *ticks,
+            LengthOfStay::Uniform { min, max } => rng.gen_range(*min..=*max),
+            LengthOfStay::Gamma { mean, shape } => Gamma::new(*shape, mean / shape)
+                .map(|gamma| gamma.sample(rng).round() as u32)
+                .unwrap_or(*mean as u32),
+        }
+    }
+}
+
+impl CareTier {
+    pub const ALL: [CareTier; 3] = [CareTier::Ward, CareTier::Icu, CareTier::Ventilator];
+
+    /// Returns the configuration of this tier, None if the tier is not configured.
+    /// The general ward is configured by hospital_capacity and hospital_period
+    /// and has no effect on survival besides the survival chance function itself.
+    pub fn config(&self) -> Option<CareTierConfig> {
+        match self {
+            CareTier::Ward => Some(CareTierConfig {
+                capacity: CONFIG.core.hospital_capacity,
+                length_of_stay: LengthOfStay::Fixed(CONFIG.core.hospital_period),
+                min_severity: 0.0,
+                risk_factor: 1.0,
+            }),
+            CareTier::Icu => CONFIG.core.icu.clone(),
+            CareTier::Ventilator => CONFIG.core.ventilator.clone(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CareTier::Ward => "ward",
+            CareTier::Icu => "icu",
+            CareTier::Ventilator => "ventilator",
+        }
+    }
+}
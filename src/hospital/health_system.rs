@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use quadtree::Positioned;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    prelude::SliceRandom,
+    rngs::StdRng,
+    SeedableRng,
+};
+use vector::Vector2;
+
+use crate::{
+    entity::{Entity, EntityId, InfectionStatus, Occupation},
+    CONFIG,
+};
+
+use super::{Admission, AdmissionPolicy, CareTier, Hospital, HospitalConfig, Patient};
+
+/// Ages from which and up to which entities can be drawn as health-care workers.
+const WORKING_AGE_MIN: u8 = 18;
+const WORKING_AGE_MAX: u8 = 65;
+
+/// All hospitals of the simulation.
+/// Entities are admitted to the nearest hospital with a free bed.
+/// Patients whose severity requires a tier their hospital has no free bed in
+/// are transferred to the nearest hospital that has one.
+/// With nosocomial transmission enabled, each hospital is staffed by health-care workers,
+/// see NosocomialConfig.
+pub struct HealthSystem {
+    hospitals: Vec<Hospital>,
+    admissions: HashMap<EntityId, usize>, // Index of the hospital each patient is in.
+    staff: Vec<Vec<EntityId>>,            // Health-care workers of each hospital.
+    policy: AdmissionPolicy,
+
+    not_eligible: u32, // Total number of positive tested entities rejected by the policy.
+    turned_away: u32,  // Total number of eligible entities rejected due to a lack of capacity.
+    preempted: u32,    // Total number of patients released early to make room for others.
+    transfers: u32,    // Total number of patients transferred between hospitals.
+    nosocomial: u32,   // Total number of infections acquired inside a hospital.
+    staff_absent: u32, // Current number of absent health-care workers.
+}
+
+impl HealthSystem {
+    /// Creates the configured hospitals.
+    /// Without any configured hospitals, a single hospital is placed in the center,
+    /// using the global tier capacities.
+    pub fn new(policy: AdmissionPolicy) -> HealthSystem {
+        let configs = if CONFIG.core.hospitals.is_empty() {
+            vec![HospitalConfig {
+                position: (
+                    CONFIG.core.dimensions.0 as f32 * 0.5,
+                    CONFIG.core.dimensions.1 as f32 * 0.5,
+                ),
+                capacity: CONFIG.core.hospital_capacity,
+                icu_capacity: CareTier::Icu.config().map_or(0, |c| c.capacity),
+                ventilator_capacity: CareTier::Ventilator.config().map_or(0, |c| c.capacity),
+            }]
+        } else {
+            CONFIG.core.hospitals.clone()
+        };
+
+        let hospitals = configs
+            .iter()
+            .map(|config| {
+                let tiers = CareTier::ALL
+                    .iter()
+                    .filter_map(|tier| tier.config().map(|tier_config| (*tier, tier_config)))
+                    .map(|(tier, mut tier_config)| {
+                        tier_config.capacity = config.capacity(tier);
+                        (tier, tier_config)
+                    })
+                    .collect();
+
+                Hospital::new(Vector2::new(config.position.0, config.position.1), tiers)
+            })
+            .collect();
+
+        HealthSystem {
+            staff: vec![Vec::new(); configs.len()],
+            hospitals,
+            admissions: HashMap::new(),
+            policy,
+            not_eligible: 0,
+            turned_away: 0,
+            preempted: 0,
+            transfers: 0,
+            nosocomial: 0,
+            staff_absent: 0,
+        }
+    }
+
+    /// Assigns health-care workers to each hospital, randomly drawn from the working age population.
+    /// Does nothing if nosocomial transmission is disabled.
+    pub fn assign_staff(&mut self, population: &mut [Entity]) {
+        let config = match &CONFIG.core.nosocomial {
+            Some(config) => config,
+            None => return,
+        };
+
+        let mut candidates: Vec<&mut Entity> = population
+            .iter_mut()
+            .filter(|entity| (WORKING_AGE_MIN..=WORKING_AGE_MAX).contains(&entity.age()))
+            .collect();
+        candidates.shuffle(&mut StdRng::from_entropy());
+
+        for (hospital, staff) in self.hospitals.iter().zip(self.staff.iter_mut()) {
+            let count = (hospital.beds() as f32 * config.staff_per_bed).ceil() as usize;
+
+            for _ in 0..count {
+                match candidates.pop() {
+                    Some(entity) => {
+                        entity.set_occupation(Occupation::HealthCareWorker);
+                        staff.push(entity.id());
+                    }
+                    None => return,
+                }
+            }
+        }
+    }
+
+    /// Counts the absent staff of each hospital and reduces its capacity accordingly.
+    /// Staff is absent while symptomatic, hospitalized or dead.
+    pub fn update_staffing<'a>(&mut self, mut entity: impl FnMut(EntityId) -> &'a Entity) {
+        self.staff_absent = 0;
+
+        for (hospital, staff) in self.hospitals.iter_mut().zip(&self.staff) {
+            if staff.is_empty() {
+                continue;
+            }
+
+            let absent = staff
+                .iter()
+                .map(|entity_id| entity(*entity_id))
+                .filter(|worker| {
+                    worker.is_dead() || worker.is_hospitalized() || worker.is_symptomatic()
+                })
+                .count();
+
+            hospital.set_staffing(1.0 - absent as f32 / staff.len() as f32);
+            self.staff_absent += absent as u32;
+        }
+    }
+
+    /// Transmits the infection between the patients and present staff of each hospital.
+    /// Every susceptible person is exposed to every infectious person in the same hospital,
+    /// weighted by their infectiousness.
+    /// Returns the newly infected entities together with their infector,
+    /// drawn by the infectiousness of the contacts, whose strain is passed on.
+    /// Does nothing if nosocomial transmission is disabled.
+    pub fn transmit<'a>(
+        &mut self,
+        mut entity: impl FnMut(EntityId) -> &'a mut Entity,
+    ) -> Vec<(EntityId, Option<EntityId>)> {
+        let chance = match &CONFIG.core.nosocomial {
+            Some(config) => config.transmission_chance,
+            None => return Vec::new(),
+        };
+
+        let mut infections = Vec::new();
+
+        for (index, hospital) in self.hospitals.iter().enumerate() {
+            let present = self.staff[index].iter().copied().filter(|entity_id| {
+                let worker = entity(*entity_id);
+                !(worker.is_dead() || worker.is_hospitalized() || worker.is_symptomatic())
+            });
+
+            let people: Vec<EntityId> = hospital
+                .patients()
+                .map(|patient| patient.entity_id())
+                .chain(present)
+                .collect();
+
+            // Chance of not escaping any of the infectious contacts,
+            // each weighted by its infectiousness.
+            let contacts: Vec<(EntityId, Option<usize>, f32)> = people
+                .iter()
+                .map(|entity_id| {
+                    let contact = entity(*entity_id);
+                    (
+                        *entity_id,
+                        contact.strain(),
+                        chance * contact.infectiousness(),
+                    )
+                })
+                .filter(|(_, _, contact_chance)| *contact_chance > 0.0)
+                .collect();
+            let escape: f32 = contacts
+                .iter()
+                .map(|(_, _, contact_chance)| 1.0 - contact_chance)
+                .product();
+
+            if escape >= 1.0 {
+                continue;
+            }
+
+            let exposure = 1.0 - escape;
+
+            for entity_id in people {
+                let person = entity(entity_id);
+
+                if *person.status() == InfectionStatus::Susceptible && person.rand() < exposure {
+                    let index = WeightedIndex::new(
+                        contacts
+                            .iter()
+                            .map(|(_, _, contact_chance)| *contact_chance),
+                    )
+                    .unwrap()
+                    .sample(person.rng());
+                    let (infector, strain, _) = contacts[index];
+
+                    person.infect(strain);
+                    self.nosocomial += 1;
+                    infections.push((entity_id, Some(infector)));
+                }
+            }
+        }
+
+        infections
+    }
+
+    /// Tries enlisting the entity into the nearest hospital with a free bed.
+    /// Preemption, if enabled by the policy, is only considered once all hospitals are full,
+    /// starting with the nearest hospital.
+    /// The preempted patients entity has to be released by the caller via its id.
+    pub fn try_hospitalize(&mut self, entity: &mut Entity) -> Admission {
+        if self.contains(entity) {
+            return Admission::AlreadyAdmitted;
+        }
+
+        if !self.policy.is_eligible(entity) {
+            self.not_eligible += 1;
+            return Admission::NotEligible;
+        }
+
+        let priority = self.policy.priority(entity);
+        let order = self.by_distance(entity.position());
+
+        for preemption in [false, true] {
+            if preemption && !self.policy.preemption {
+                break;
+            }
+
+            for index in &order {
+                let admission =
+                    self.hospitals[*index].try_hospitalize(entity, priority, preemption);
+
+                match admission {
+                    Admission::TurnedAway => continue,
+                    Admission::Preempted(entity_id) => {
+                        self.admissions.remove(&entity_id);
+                        self.preempted += 1;
+                    }
+                    _ => {}
+                }
+
+                self.admissions.insert(entity.id(), *index);
+                return admission;
+            }
+        }
+
+        self.turned_away += 1;
+        Admission::TurnedAway
+    }
+
+    /// Returns the indices of all hospitals, ordered by their distance to the given position.
+    fn by_distance(&self, position: &Vector2<f32>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.hospitals.len()).collect();
+        order.sort_by(|a, b| {
+            let a = self.hospitals[*a].position().distance(position);
+            let b = self.hospitals[*b].position().distance(position);
+            a.total_cmp(&b)
+        });
+        order
+    }
+
+    /// Returns the admission priority of the entity under the admission policy.
+    pub fn priority(&self, entity: &Entity) -> f32 {
+        self.policy.priority(entity)
+    }
+
+    /// Returns true if any hospital has a free bed the given entity may be admitted to.
+    pub fn has_bed_for(&self, entity: &Entity) -> bool {
+        let severity = entity.severity();
+        self.hospitals
+            .iter()
+            .any(|hospital| hospital.has_bed_for(severity))
+    }
+
+    /// Returns true if the given entity is in any hospital.
+    pub fn contains(&self, entity: &Entity) -> bool {
+        self.admissions.contains_key(&entity.id())
+    }
+
+    /// Returns the current count of entities in all hospitals.
+    pub fn count(&self) -> usize {
+        self.admissions.len()
+    }
+
+    /// Returns the current count of entities in the given tier of all hospitals.
+    pub fn occupancy(&self, tier: CareTier) -> usize {
+        self.hospitals
+            .iter()
+            .map(|hospital| hospital.occupancy(tier))
+            .sum()
+    }
+
+    pub fn hospitals(&self) -> &Vec<Hospital> {
+        &self.hospitals
+    }
+
+    /// Returns the total number of positive tested entities rejected by the admission policy.
+    pub fn not_eligible(&self) -> u32 {
+        self.not_eligible
+    }
+
+    /// Returns the total number of eligible entities rejected because all hospitals were full.
+    pub fn turned_away(&self) -> u32 {
+        self.turned_away
+    }
+
+    /// Returns the total number of patients released early to admit higher priority entities.
+    pub fn preempted(&self) -> u32 {
+        self.preempted
+    }
+
+    /// Returns the total number of patients transferred between hospitals.
+    pub fn transfers(&self) -> u32 {
+        self.transfers
+    }
+
+    /// Returns the total number of infections acquired inside a hospital.
+    pub fn nosocomial(&self) -> u32 {
+        self.nosocomial
+    }
+
+    /// Returns the current number of absent health-care workers.
+    pub fn staff_absent(&self) -> u32 {
+        self.staff_absent
+    }
+
+    /// Updates every hospital, afterwards transfers patients who could not be escalated
+    /// within their hospital to the nearest hospital with a free bed in a higher tier.
+    pub fn update<'a>(&mut self, mut entity: impl FnMut(EntityId) -> &'a mut Entity) {
+        for hospital in &mut self.hospitals {
+            hospital.update(&mut entity);
+        }
+
+        if self.hospitals.len() < 2 {
+            return;
+        }
+
+        for from in 0..self.hospitals.len() {
+            let escalations = self.hospitals[from].escalations(|entity_id| &*entity(entity_id));
+
+            for (entity_id, current, required) in escalations {
+                let target = self
+                    .by_distance(self.hospitals[from].position())
+                    .into_iter()
+                    .filter(|to| *to != from)
+                    .find_map(|to| {
+                        self.hospitals[to]
+                            .free_tier(current, required)
+                            .filter(|tier| *tier > current)
+                            .map(|tier| (to, tier))
+                    });
+
+                if let Some((to, tier)) = target {
+                    let patient = self.hospitals[from].take(entity_id).unwrap();
+                    self.hospitals[to].receive(patient, tier, entity(entity_id));
+                    self.admissions.insert(entity_id, to);
+                    self.transfers += 1;
+                }
+            }
+        }
+    }
+
+    /// Releases the given entity from its hospital.
+    /// Sets the corresponding flag on the entity.
+    pub fn release(&mut self, entity: &mut Entity) {
+        if let Some(index) = self.admissions.remove(&entity.id()) {
+            self.hospitals[index].release(entity);
+        }
+    }
+
+    /// Returns true if the given entity is ready to be released
+    /// aka its timer has run out.
+    pub fn ready_to_release(&self, entity: &Entity) -> bool {
+        self.admissions
+            .get(&entity.id())
+            .map(|index| self.hospitals[*index].ready_to_release(entity))
+            .unwrap_or(false)
+    }
+
+    /// Returns all patients currently in any hospital.
+    /// Use patient_entity to look up a patients entity.
+    pub fn patients(&self) -> impl Iterator<Item = &Patient> {
+        self.hospitals
+            .iter()
+            .flat_map(|hospital| hospital.patients())
+    }
+
+    /// Returns the entity of the given id from the population if it is a patient of any hospital.
+    pub fn patient_entity<'a>(
+        &self,
+        entity_id: EntityId,
+        population: &'a [Entity],
+    ) -> Option<&'a Entity> {
+        self.admissions
+            .get(&entity_id)
+            .and_then(|index| self.hospitals[*index].patient_entity(entity_id, population))
+    }
+}
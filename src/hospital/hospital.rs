@@ -1,45 +1,127 @@
-use crate::{entity::Entity, CONFIG};
+use std::collections::HashMap;
 
-use super::Patient;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use vector::Vector2;
+
+use crate::entity::{Entity, EntityId};
+
+use super::{CareTier, CareTierConfig, Patient};
+
+/// Outcome of trying to hospitalize an entity.
+pub enum Admission {
+    Admitted,
+    Preempted(EntityId), // Admitted by releasing the patient with the given entity id.
+    AlreadyAdmitted,
+    NotEligible, // Rejected by the admission policy.
+    TurnedAway,  // Rejected because the hospital is full.
+}
+
+/// Location and capacities of a single hospital.
+/// The remaining tier parameters are shared by all hospitals.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HospitalConfig {
+    pub position: (f32, f32),
+    pub capacity: u32, // Beds in the general ward.
+    #[serde(default)]
+    pub icu_capacity: u32,
+    #[serde(default)]
+    pub ventilator_capacity: u32,
+}
+
+impl HospitalConfig {
+    /// Returns the number of beds of the given tier.
+    pub fn capacity(&self, tier: CareTier) -> u32 {
+        match tier {
+            CareTier::Ward => self.capacity,
+            CareTier::Icu => self.icu_capacity,
+            CareTier::Ventilator => self.ventilator_capacity,
+        }
+    }
+}
 
 /// Used to enlist and release entitys to the hospital.
+/// Patients are cared for in tiers of increasing intensity.
+/// A patient escalates as soon as its severity requires a higher tier and a bed is free there.
+/// It stays in a tier for at least the tiers length of stay, afterwards it steps down
+/// once its severity no longer requires the tier. Patients are released from the general ward.
+/// Without sufficient staff, only the staffed fraction of each tiers beds can be filled.
 pub struct Hospital {
-    capacity: usize,
-    enlisted: Vec<Patient>,
+    position: Vector2<f32>,
+    tiers: Vec<(CareTier, CareTierConfig)>, // Configured tiers, ordered from least to most intensive.
+    occupancy: HashMap<CareTier, usize>,
+    staffing: f32, // Fraction of the staff present, 1.0 without nosocomial transmission.
+    enlisted: HashMap<EntityId, Patient>,
+
+    rng: StdRng,
 }
 
 impl Hospital {
-    pub fn new(capacity: usize) -> Hospital {
+    pub fn new(position: Vector2<f32>, mut tiers: Vec<(CareTier, CareTierConfig)>) -> Hospital {
+        tiers.sort_by_key(|(tier, _)| *tier);
+
+        let capacity = tiers
+            .iter()
+            .map(|(_, config)| config.capacity as usize)
+            .sum();
+
         Hospital {
-            capacity,
-            enlisted: Vec::with_capacity(capacity as usize),
+            position,
+            occupancy: tiers.iter().map(|(tier, _)| (*tier, 0)).collect(),
+            tiers,
+            staffing: 1.0,
+            enlisted: HashMap::with_capacity(capacity),
+            rng: StdRng::from_entropy(),
         }
     }
 
-    /// Tries enlisting the entity into the hospital.
-    /// Fails if the hospital is full.
+    /// Tries enlisting the entity into the hospital with the given admission priority.
+    /// The entity is placed in the tier its severity requires,
+    /// or the most intensive lower tier with a free bed.
+    /// If no such bed is free and preemption is allowed, the lowest priority patient
+    /// of these tiers is released if the entity has a higher priority.
+    /// The preempted patients entity has to be released by the caller via its id.
     /// Sets the corresponding flag on the entity.
     pub fn try_hospitalize(
         &mut self,
         entity: &mut Entity,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if !self.is_full() && !self.contains(entity) {
-            self.enlisted.push(Patient::new(
-                entity as *const _ as usize,
-                CONFIG.core.hospital_period,
-            ));
-            entity.set_hospitalized();
-            Ok(())
-        } else {
-            Err(format!("Hospital is full").into())
-        }
+        priority: f32,
+        preemption: bool,
+    ) -> Admission {
+        let required = self.required_tier(entity.severity());
+        let mut admission = Admission::Admitted;
+
+        let tier = match self.free_tier(CareTier::Ward, required) {
+            Some(tier) => tier,
+            None => {
+                let lowest = self
+                    .enlisted
+                    .values()
+                    .filter(|patient| patient.tier <= required)
+                    .min_by(|a, b| a.priority.total_cmp(&b.priority))
+                    .map(|patient| (patient.entity_id, patient.priority, patient.tier));
+
+                match lowest {
+                    Some((entity_id, lowest_priority, tier))
+                        if preemption && priority > lowest_priority =>
+                    {
+                        self.take(entity_id);
+                        admission = Admission::Preempted(entity_id);
+                        tier
+                    }
+                    _ => return Admission::TurnedAway,
+                }
+            }
+        };
+
+        self.receive(Patient::new(entity.id(), tier, 0, priority), tier, entity);
+
+        admission
     }
 
-    /// Returns true if the given entity is already in the hospital.
-    pub fn contains(&self, entity: &Entity) -> bool {
-        self.enlisted
-            .iter()
-            .any(|patient| patient.entity_id == entity as *const _ as usize)
+    /// Returns the position of the hospital.
+    pub fn position(&self) -> &Vector2<f32> {
+        &self.position
     }
 
     /// Returns the current count of entities in the hospital.
@@ -47,29 +129,116 @@ impl Hospital {
         self.enlisted.len()
     }
 
-    /// Returns true if the hospital is full.
+    /// Returns the current count of entities in the given tier.
+    pub fn occupancy(&self, tier: CareTier) -> usize {
+        self.occupancy.get(&tier).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of beds over all tiers, regardless of staffing.
+    pub fn beds(&self) -> u32 {
+        self.tiers.iter().map(|(_, config)| config.capacity).sum()
+    }
+
+    /// Sets the fraction of the staff present.
+    pub fn set_staffing(&mut self, staffing: f32) {
+        self.staffing = staffing.clamp(0.0, 1.0);
+    }
+
+    /// Returns the number of beds of the given tier that can currently be filled.
+    fn capacity(&self, config: &CareTierConfig) -> usize {
+        (config.capacity as f32 * self.staffing).floor() as usize
+    }
+
+    /// Returns true if there is no free bed in any tier.
     pub fn is_full(&self) -> bool {
-        self.enlisted.len() >= self.capacity
+        self.tiers
+            .iter()
+            .all(|(tier, config)| self.occupancy(*tier) >= self.capacity(config))
     }
 
-    /// Counts each entitys hospitalized-timer down
-    pub fn update(&mut self) {
-        for patient in &mut self.enlisted {
+    /// Returns true if a bed the given severity may be admitted to is free,
+    /// i.e. one in its required tier or a lower one.
+    pub fn has_bed_for(&self, severity: f32) -> bool {
+        self.free_tier(CareTier::Ward, self.required_tier(severity))
+            .is_some()
+    }
+
+    /// Counts each entitys hospitalized-timer down and moves patients between tiers.
+    /// The most severe patients are considered first for scarce intensive care beds.
+    pub fn update<'a>(&mut self, mut entity: impl FnMut(EntityId) -> &'a mut Entity) {
+        let mut patients: Vec<(EntityId, f32)> = self
+            .enlisted
+            .keys()
+            .map(|entity_id| (*entity_id, entity(*entity_id).severity()))
+            .collect();
+        patients.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (entity_id, severity) in patients {
+            let patient = self.enlisted.get_mut(&entity_id).unwrap();
             patient.tick();
+
+            let current = patient.tier;
+            let stay_over = patient.stay_over();
+            let required = self.required_tier(severity);
+
+            let next = if required > current {
+                self.free_tier(current, required)
+                    .filter(|tier| *tier != current)
+            } else if required < current && stay_over {
+                self.free_tier(required, current)
+                    .filter(|tier| *tier != current)
+            } else {
+                None
+            };
+
+            if let Some(next) = next {
+                let patient = self.take(entity_id).unwrap();
+                self.receive(patient, next, entity(entity_id));
+            }
         }
     }
 
+    /// Returns the patients whose severity requires a more intensive tier than they are in,
+    /// together with their current and required tier.
+    pub fn escalations<'a>(
+        &self,
+        mut entity: impl FnMut(EntityId) -> &'a Entity,
+    ) -> Vec<(EntityId, CareTier, CareTier)> {
+        self.enlisted
+            .values()
+            .map(|patient| {
+                let required = self.required_tier(entity(patient.entity_id).severity());
+                (patient.entity_id, patient.tier, required)
+            })
+            .filter(|(_, current, required)| required > current)
+            .collect()
+    }
+
+    /// Removes the patient of the given entity from the hospital, e.g. to transfer it.
+    /// Does not change the entity.
+    pub fn take(&mut self, entity_id: EntityId) -> Option<Patient> {
+        let patient = self.enlisted.remove(&entity_id)?;
+        *self.occupancy.get_mut(&patient.tier).unwrap() -= 1;
+        Some(patient)
+    }
+
+    /// Places the patient in the given tier of this hospital,
+    /// its stay starts anew with the tiers length of stay.
+    pub fn receive(&mut self, mut patient: Patient, tier: CareTier, entity: &mut Entity) {
+        patient.tier = tier;
+        patient.time_in_tier = self.length_of_stay(tier);
+
+        *self.occupancy.get_mut(&tier).unwrap() += 1;
+        self.enlisted.insert(patient.entity_id, patient);
+
+        entity.set_hospitalized(tier);
+    }
+
     /// Releases the given entity from the hospital.
     /// Sets the corresponding flag on the entity.
     pub fn release(&mut self, entity: &mut Entity) {
-        let mut index = 0;
-        for patient in &mut self.enlisted {
-            if patient.entity_id == entity as *const _ as usize {
-                self.enlisted.remove(index);
-                entity.unset_hospitalized();
-                break;
-            }
-            index += 1;
+        if self.take(entity.id()).is_some() {
+            entity.unset_hospitalized();
         }
     }
 
@@ -77,9 +246,54 @@ impl Hospital {
     /// aka its timer has run out.
     pub fn ready_to_release(&self, entity: &Entity) -> bool {
         self.enlisted
-            .iter()
-            .find(|p| p.entity_id == entity as *const _ as usize)
+            .get(&entity.id())
             .map(|p| p.ready_to_release())
             .unwrap_or(false)
     }
+
+    /// Returns all patients currently in the hospital.
+    /// Use patient_entity to look up a patients entity.
+    pub fn patients(&self) -> impl Iterator<Item = &Patient> {
+        self.enlisted.values()
+    }
+
+    /// Returns the entity of the given id from the population if it is a patient of the hospital.
+    pub fn patient_entity<'a>(
+        &self,
+        entity_id: EntityId,
+        population: &'a [Entity],
+    ) -> Option<&'a Entity> {
+        self.enlisted
+            .contains_key(&entity_id)
+            .then(|| population.get(entity_id))
+            .flatten()
+    }
+
+    /// Returns the most intensive configured tier the given severity requires.
+    fn required_tier(&self, severity: f32) -> CareTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|(_, config)| severity >= config.min_severity)
+            .map(|(tier, _)| *tier)
+            .unwrap_or(CareTier::Ward)
+    }
+
+    /// Returns the most intensive tier within [lowest, highest] with a free bed.
+    pub fn free_tier(&self, lowest: CareTier, highest: CareTier) -> Option<CareTier> {
+        self.tiers
+            .iter()
+            .rev()
+            .filter(|(tier, _)| lowest <= *tier && *tier <= highest)
+            .find(|(tier, config)| self.occupancy(*tier) < self.capacity(config))
+            .map(|(tier, _)| *tier)
+    }
+
+    fn length_of_stay(&mut self, tier: CareTier) -> u32 {
+        self.tiers
+            .iter()
+            .find(|(configured, _)| *configured == tier)
+            .map(|(_, config)| config.length_of_stay.sample(&mut self.rng))
+            .unwrap_or(0)
+    }
 }
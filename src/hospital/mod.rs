@@ -1,5 +1,15 @@
+mod admission_policy;
+mod care_tier;
+mod health_system;
 mod hospital;
+mod nosocomial;
+mod overflow_mortality;
 mod patient;
 
-pub use hospital::Hospital;
+pub use admission_policy::AdmissionPolicy;
+pub use care_tier::{CareTier, CareTierConfig};
+pub use health_system::HealthSystem;
+pub use hospital::{Admission, Hospital, HospitalConfig};
+pub use nosocomial::NosocomialConfig;
+pub use overflow_mortality::OverflowMortality;
 pub use patient::Patient;
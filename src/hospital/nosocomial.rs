@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Hospital-acquired infections.
+/// Each hospital is staffed by health-care workers drawn from the working age population.
+/// Patients and present staff of a hospital are in contact with each other every tick.
+/// Absent staff, i.e. staff that is symptomatic, hospitalized or dead,
+/// reduces the capacity of their hospital proportionally.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NosocomialConfig {
+    pub staff_per_bed: f32, // Health-care workers per bed, summed over all tiers.
+    pub transmission_chance: f32, // Chance per tick of being infected by each infectious contact in the hospital.
+}
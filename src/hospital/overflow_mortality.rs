@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Penalty for severe cases that are turned away or preempted because the hospital is full.
+/// Severe cases that are not hospitalized are denied care as well while no hospital has a bed for them,
+/// whether they were tested or not.
+/// Such entities have to survive an additional mortality roll at the end of their infection,
+/// deaths caused by it are attributed to care not being available.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OverflowMortality {
+    pub min_severity: f32, // Severity from which a case counts as severe.
+    pub mortality: f32,    // Additional chance of dying without care.
+}
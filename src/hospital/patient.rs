@@ -1,23 +1,41 @@
+use crate::entity::EntityId;
+
+use super::CareTier;
+
 /// Struct representing a patient enlisted in the hospital
-/// Uses raw pointers converted to usize to identify the entity.
+/// Uses the stable entity id to identify the entity.
 pub struct Patient {
-    pub(super) entity_id: usize,
-    pub(super) time_to_recover: u32,
+    pub(super) entity_id: EntityId,
+    pub(super) tier: CareTier,
+    pub(super) time_in_tier: u32, // Remaining minimum stay in the current tier.
+    pub(super) priority: f32,     // Admission priority, used for preemption.
 }
 
 impl Patient {
-    pub fn new(entity_id: usize, time_to_recover: u32) -> Patient {
+    pub fn new(entity_id: EntityId, tier: CareTier, time_in_tier: u32, priority: f32) -> Patient {
         Patient {
             entity_id,
-            time_to_recover,
+            tier,
+            time_in_tier,
+            priority,
         }
     }
 
+    pub fn entity_id(&self) -> EntityId {
+        self.entity_id
+    }
+
     pub fn tick(&mut self) {
-        self.time_to_recover -= 1;
+        self.time_in_tier = self.time_in_tier.saturating_sub(1);
+    }
+
+    /// Returns true if the minimum stay in the current tier is over.
+    pub fn stay_over(&self) -> bool {
+        self.time_in_tier == 0
     }
 
+    /// Patients are only released from the general ward.
     pub fn ready_to_release(&self) -> bool {
-        self.time_to_recover <= 0
+        self.tier == CareTier::Ward && self.stay_over()
     }
 }
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use crate::CONFIG;
+
+use super::Schedule;
+
+/// Mobility reduction. While in effect, the complying entities stop moving
+/// and the others move at a reduced maximum velocity.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Lockdown {
+    pub max_velocity: f32, // Replaces the maximum velocity, if lower.
+    pub mobile: f32,       // Fraction of the mobile entities that keep moving.
+    pub schedule: Schedule,
+}
+
+impl Lockdown {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !(0.0..=1.0).contains(&self.mobile) {
+            return Err(format!(
+                "the mobile fraction {} of the lockdown has to be within [0, 1]",
+                self.mobile
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the lockdown configuration if a lockdown is in effect at the given time.
+    pub fn active(time: u32) -> Option<&'static Lockdown> {
+        CONFIG
+            .core
+            .lockdown
+            .as_ref()
+            .filter(|lockdown| lockdown.schedule.is_active(time))
+    }
+}
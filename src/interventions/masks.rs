@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{entity::Entity, CONFIG};
+
+use super::Schedule;
+
+/// Mask wearing. While in effect, the complying entities wear masks,
+/// which reduce the chance of infection of each contact they are part of.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Masks {
+    pub compliance: f32,          // Fraction of the entities wearing a mask.
+    pub source_protection: f32, // Reduction of the chance of an infected mask wearer infecting others.
+    pub receiver_protection: f32, // Reduction of the chance of a mask wearer being infected.
+    pub schedule: Schedule,
+}
+
+impl Masks {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for (name, value) in [
+            ("compliance", self.compliance),
+            ("source_protection", self.source_protection),
+            ("receiver_protection", self.receiver_protection),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("the mask {} {} has to be within [0, 1]", name, value).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the mask configuration if masks are in effect at the given time.
+    pub fn active(time: u32) -> Option<&'static Masks> {
+        CONFIG
+            .core
+            .masks
+            .as_ref()
+            .filter(|masks| masks.schedule.is_active(time))
+    }
+
+    /// Returns the factor by which masks scale the chance of source infecting receiver.
+    pub fn protection(&self, source: &Entity, receiver: &Entity) -> f32 {
+        let mut factor = 1.0;
+
+        if source.complies(self.compliance) {
+            factor *= 1.0 - self.source_protection;
+        }
+        if receiver.complies(self.compliance) {
+            factor *= 1.0 - self.receiver_protection;
+        }
+
+        factor
+    }
+}
@@ -0,0 +1,7 @@
+mod lockdown;
+mod masks;
+mod schedule;
+
+pub use lockdown::Lockdown;
+pub use masks::Masks;
+pub use schedule::Schedule;
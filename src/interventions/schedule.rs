@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Periods in which an intervention is in effect, as [start, end) ticks.
+/// Written as a plain list, e.g. [(100, 300), (500, 600)].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Schedule(Vec<(u32, u32)>);
+
+impl Schedule {
+    pub fn is_active(&self, time: u32) -> bool {
+        self.0
+            .iter()
+            .any(|(start, end)| *start <= time && time < *end)
+    }
+}
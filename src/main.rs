@@ -1,16 +1,25 @@
+use compartmental::Gillespie;
 use config::Config;
+use engine::EngineKind;
 use lazy_static::lazy_static;
 use quadtree::Positioned;
 use runner::NoGraphics;
 use runner::Runner;
 use runner::SDL;
 
+mod compartmental;
 mod config;
+mod engine;
 mod entity;
 mod hospital;
+mod interventions;
+mod movement;
+mod network;
 mod runner;
 mod simulator;
 mod statistics;
+mod testing;
+mod transmissions;
 mod util;
 
 lazy_static! {
@@ -20,18 +29,29 @@ lazy_static! {
             if entity.is_hospitalized() {
                 0.99
             } else {
-                1.0 - (entity.age() as f32 / 200.0)
+                // Each risk factor, e.g. a comorbidity, raises the chance of dying.
+                (1.0 - (entity.age() as f32 / 200.0))
+                    * 0.95f32.powi(entity.risk_factor_count() as i32)
             }
         },
+        // Chance of a infecting b during a single tick, within the infection radius.
         |a, b| {
             let dist = a.position().distance(b.position());
-            1.0 / (dist * dist * dist * dist)
+            0.05 * (-dist / 5.0).exp() * a.infectiousness()
         },
     )
     .unwrap();
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Engines other than the spatial simulator have nothing to render.
+    if let EngineKind::Gillespie(config) = &CONFIG.core.engine {
+        let mut engine = Gillespie::new(config.clone());
+        let reference = engine.reference();
+        runner::run_engine(&mut engine, reference, true, true, true);
+        return Ok(());
+    }
+
     let simulator = simulator::Simulator::new(num_cpus::get() as u32);
 
     // If you dont need the graphics, you can use NoGraphics.
@@ -0,0 +1,23 @@
+use vector::Vector2;
+
+use crate::entity::Entity;
+
+use super::MovementModel;
+
+/// Random walk with normally distributed steps.
+/// The standard deviation along each axis is half the maximum velocity,
+/// steps longer than the maximum velocity are shortened to it.
+pub struct Brownian;
+
+impl MovementModel for Brownian {
+    fn step(&self, entity: &mut Entity, max_velocity: f32) {
+        // Box-Muller transform of two uniform samples into two independent normal samples.
+        let radius = (-2.0 * (1.0 - entity.rand()).ln()).sqrt() * 0.5 * max_velocity;
+        let angle = entity.rand() * std::f32::consts::TAU;
+
+        let mut offset = Vector2::new(radius * angle.cos(), radius * angle.sin());
+        offset.clamp_mag(max_velocity);
+
+        entity.displace(offset);
+    }
+}
@@ -0,0 +1,20 @@
+use quadtree::Positioned;
+
+use crate::entity::Entity;
+
+use super::MovementModel;
+
+/// Force based movement, entities drift at their velocity and are repelled by nearby entities,
+/// simulating distancing.
+pub struct Force;
+
+impl MovementModel for Force {
+    fn interact(&self, entity: &mut Entity, other: &Entity, delta_time: f32) {
+        let diff = *entity.position() - *other.position();
+        entity.apply_force(diff * 0.05 * delta_time);
+    }
+
+    fn step(&self, entity: &mut Entity, max_velocity: f32) {
+        entity.integrate(max_velocity);
+    }
+}
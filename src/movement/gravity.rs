@@ -0,0 +1,42 @@
+use quadtree::Positioned;
+use serde::{Deserialize, Serialize};
+use vector::Vector2;
+
+use crate::entity::Entity;
+
+use super::{Force, MovementModel};
+
+/// A place attracting entities, e.g. a city center.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Hub {
+    pub position: (f32, f32),
+    pub mass: f32, // Attraction relative to the other hubs.
+}
+
+/// Force based movement with entities pulled toward the hubs,
+/// by the mass of a hub over the squared distance.
+pub struct Gravity {
+    pub hubs: Vec<Hub>,
+    pub strength: f32, // Scales the attraction of all hubs.
+}
+
+impl MovementModel for Gravity {
+    fn interact(&self, entity: &mut Entity, other: &Entity, delta_time: f32) {
+        Force.interact(entity, other, delta_time);
+    }
+
+    fn step(&self, entity: &mut Entity, max_velocity: f32) {
+        let position = *entity.position();
+
+        for hub in &self.hubs {
+            let hub_position = Vector2::new(hub.position.0, hub.position.1);
+            // Entities close to a hub are not accelerated beyond all bounds.
+            let distance = position.distance(&hub_position).max(1.0);
+            let direction = (hub_position - position) * (1.0 / distance);
+
+            entity.apply_force(direction * (self.strength * hub.mass / (distance * distance)));
+        }
+
+        entity.integrate(max_velocity);
+    }
+}
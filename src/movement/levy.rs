@@ -0,0 +1,29 @@
+use vector::Vector2;
+
+use crate::{entity::Entity, CONFIG};
+
+use super::MovementModel;
+
+/// Levy walk, flights in random directions with power law distributed lengths,
+/// mostly short flights with rare long ones. Flights are travelled at the maximum velocity.
+pub struct Levy {
+    pub exponent: f32, // Exponent of the flight length distribution, in (1, 3]. Smaller values fly further.
+    pub max_step: f32, // Longest possible flight. [pixels]
+}
+
+impl MovementModel for Levy {
+    /// Flights are at least as long as a single tick at CONFIG.core.max_velocity.
+    fn step(&self, entity: &mut Entity, max_velocity: f32) {
+        if entity.flight() <= 0.0 {
+            // Inverse transform sampling of a Pareto distribution.
+            let length = (CONFIG.core.max_velocity
+                * (1.0 - entity.rand()).powf(-1.0 / (self.exponent - 1.0)))
+            .min(self.max_step);
+            let angle = entity.rand() * std::f32::consts::TAU;
+
+            entity.start_flight(Vector2::new(angle.cos(), angle.sin()), length);
+        }
+
+        entity.continue_flight(max_velocity);
+    }
+}
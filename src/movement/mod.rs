@@ -0,0 +1,15 @@
+mod brownian;
+mod force;
+mod gravity;
+mod levy;
+mod movement;
+mod movement_model;
+mod stationary;
+
+pub use brownian::Brownian;
+pub use force::Force;
+pub use gravity::{Gravity, Hub};
+pub use levy::Levy;
+pub use movement::Movement;
+pub use movement_model::MovementModel;
+pub use stationary::Stationary;
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Brownian, Force, Gravity, Hub, Levy, MovementModel, Stationary};
+
+/// Movement model selected in the configuration, see MovementModel.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum Movement {
+    #[default]
+    Force, // Drifting entities repelling each other.
+    Brownian,
+    Levy {
+        exponent: f32,
+        max_step: f32,
+    },
+    Gravity {
+        hubs: Vec<Hub>,
+        strength: f32,
+    },
+    Stationary,
+}
+
+impl Movement {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Movement::Levy { exponent, .. } if *exponent <= 1.0 => {
+                Err(format!("the levy exponent {} has to be greater than 1", exponent).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn model(&self) -> Box<dyn MovementModel> {
+        match self {
+            Movement::Force => Box::new(Force),
+            Movement::Brownian => Box::new(Brownian),
+            Movement::Levy { exponent, max_step } => Box::new(Levy {
+                exponent: *exponent,
+                max_step: *max_step,
+            }),
+            Movement::Gravity { hubs, strength } => Box::new(Gravity {
+                hubs: hubs.clone(),
+                strength: *strength,
+            }),
+            Movement::Stationary => Box::new(Stationary),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use crate::entity::Entity;
+
+/// Describes how entities move through the simulation space.
+/// Mobility, lockdowns and compliance are handled by Entity::update_movement,
+/// models only decide where mobile entities go.
+pub trait MovementModel: Send + Sync {
+    /// Reacts to another entity within the infection radius, e.g. by keeping distance.
+    /// Entities ignore each other by default.
+    fn interact(&self, _entity: &mut Entity, _other: &Entity, _delta_time: f32) {}
+
+    /// Moves the entity by a single tick at no more than the given velocity.
+    fn step(&self, entity: &mut Entity, max_velocity: f32);
+}
@@ -0,0 +1,10 @@
+use crate::entity::Entity;
+
+use super::MovementModel;
+
+/// Entities stay where they are.
+pub struct Stationary;
+
+impl MovementModel for Stationary {
+    fn step(&self, _entity: &mut Entity, _max_velocity: f32) {}
+}
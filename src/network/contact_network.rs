@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// Contacts as edges of a graph instead of the proximity of the entities.
+/// Infections spread only along the edges, regardless of the positions of the entities.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ContactNetwork {
+    pub graph: Graph,
+    /// Chance of an infected entity infecting a susceptible neighbour during a single tick.
+    /// Scaled by the infectiousness of the infected entity.
+    pub transmission_chance: f32,
+}
+
+/// How the graph is built. Entities are nodes, identified by their index in the population.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Graph {
+    ErdosRenyi {
+        mean_degree: f32,
+    }, // Uniformly random edges.
+    /// A ring connecting each entity to its nearest neighbours, whose edges are rewired
+    /// at random with the given chance, yielding a small world.
+    WattsStrogatz {
+        neighbours: usize,
+        rewiring: f32,
+    },
+    /// Preferential attachment, each entity connects to the given number of entities
+    /// chosen by their degree, yielding a scale-free network.
+    BarabasiAlbert {
+        edges: usize,
+    },
+    /// Edge list file with one edge per line, as two entity indices separated by whitespace or a comma.
+    /// The path is relative to the configuration folder.
+    File(String),
+}
+
+impl Graph {
+    pub fn validate(&self, population_size: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let population_size = population_size as usize;
+
+        match self {
+            // A complete graph has a degree of population_size - 1.
+            Graph::ErdosRenyi { mean_degree }
+                if !(0.0..=(population_size as f32 - 1.0)).contains(mean_degree) =>
+            {
+                Err(format!(
+                    "the mean degree {} has to be within [0, {}]",
+                    mean_degree,
+                    population_size.saturating_sub(1)
+                )
+                .into())
+            }
+            Graph::WattsStrogatz { neighbours, .. }
+                if neighbours % 2 != 0 || *neighbours >= population_size =>
+            {
+                Err(format!(
+                    "the number of neighbours {} has to be even and smaller than the population size",
+                    neighbours
+                )
+                .into())
+            }
+            Graph::BarabasiAlbert { edges } if *edges == 0 || *edges >= population_size => {
+                Err(format!(
+                    "the number of edges {} has to be positive and smaller than the population size",
+                    edges
+                )
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
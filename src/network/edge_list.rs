@@ -0,0 +1,84 @@
+use std::fs;
+
+use crate::entity::EntityId;
+
+/// An edge list file stores the contacts of a network, one edge per line.
+/// Lines starting with # are comments.
+pub struct EdgeList;
+
+impl EdgeList {
+    /// Loads the edges from the given file.
+    /// Both entities of an edge have to be part of a population of the given size.
+    pub fn load(
+        path: &str,
+        population_size: usize,
+    ) -> Result<Vec<(EntityId, EntityId)>, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        let mut edges = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let edge = EdgeList::parse_edge(line, population_size)
+                .map_err(|message| format!("{}: line {}: {}", path, index + 1, message))?;
+            edges.push(edge);
+        }
+
+        Ok(edges)
+    }
+
+    fn parse_edge(line: &str, population_size: usize) -> Result<(EntityId, EntityId), String> {
+        let parts: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.len() != 2 {
+            return Err(format!("expected 2 entities, found {}", parts.len()));
+        }
+
+        let parse_entity = |value: &str| {
+            let entity_id = value
+                .parse::<EntityId>()
+                .map_err(|_| format!("invalid entity '{}'", value))?;
+            if entity_id >= population_size {
+                return Err(format!(
+                    "entity {} is outside of the population of size {}",
+                    entity_id, population_size
+                ));
+            }
+            Ok(entity_id)
+        };
+
+        Ok((parse_entity(parts[0])?, parse_entity(parts[1])?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_edges() {
+        assert_eq!(EdgeList::parse_edge("1 2", 3), Ok((1, 2)));
+        assert_eq!(EdgeList::parse_edge("0,\t2", 3), Ok((0, 2)));
+        assert!(EdgeList::parse_edge("1", 3).is_err());
+        assert!(EdgeList::parse_edge("1 x", 3).is_err());
+        assert!(EdgeList::parse_edge("1 3", 3).is_err());
+    }
+
+    #[test]
+    fn reports_the_line_of_an_invalid_edge() {
+        let path = std::env::temp_dir().join("episim_edge_list_test.txt");
+        fs::write(&path, "# contacts\n0 1\n\n1 5\n").unwrap();
+
+        let error = EdgeList::load(path.to_str().unwrap(), 3).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        let message = error.to_string();
+        assert!(message.contains("line 4"), "{}", message);
+        assert!(message.contains("entity 5 is outside"), "{}", message);
+    }
+}
@@ -0,0 +1,7 @@
+mod contact_network;
+mod edge_list;
+mod network;
+
+pub use contact_network::{ContactNetwork, Graph};
+pub use edge_list::EdgeList;
+pub use network::Network;
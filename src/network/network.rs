@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::{entity::EntityId, CONFIG};
+
+use super::Graph;
+
+/// Undirected contact graph over the initial population.
+/// Entities added during the simulation, i.e. newborns and travelers, have no contacts.
+pub struct Network {
+    neighbours: Vec<Vec<EntityId>>,
+}
+
+impl Network {
+    /// Builds the configured graph over a population of the given size.
+    pub fn new(graph: &Graph, size: usize, rng: &mut StdRng) -> Network {
+        let edges = match graph {
+            Graph::ErdosRenyi { mean_degree } => Network::erdos_renyi(size, *mean_degree, rng),
+            Graph::WattsStrogatz {
+                neighbours,
+                rewiring,
+            } => Network::watts_strogatz(size, *neighbours, *rewiring, rng),
+            Graph::BarabasiAlbert { edges } => Network::barabasi_albert(size, *edges, rng),
+            Graph::File(_) => CONFIG
+                .network_edges()
+                .expect("The edge list is loaded with the config.")
+                .clone(),
+        };
+
+        let mut neighbours = vec![Vec::new(); size];
+        for (a, b) in edges {
+            if a != b {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+
+        Network { neighbours }
+    }
+
+    /// Random graph with a fixed number of edges, yielding the given mean degree.
+    fn erdos_renyi(size: usize, mean_degree: f32, rng: &mut StdRng) -> Vec<(EntityId, EntityId)> {
+        // Never more edges than the complete graph has.
+        let count = ((size as f32 * mean_degree / 2.0).round() as usize)
+            .min(size * size.saturating_sub(1) / 2);
+        let mut edges = HashSet::with_capacity(count);
+
+        while edges.len() < count {
+            let a = rng.gen_range(0..size);
+            let b = rng.gen_range(0..size);
+            if a != b {
+                edges.insert((a.min(b), a.max(b)));
+            }
+        }
+
+        edges.into_iter().collect()
+    }
+
+    fn watts_strogatz(
+        size: usize,
+        neighbours: usize,
+        rewiring: f32,
+        rng: &mut StdRng,
+    ) -> Vec<(EntityId, EntityId)> {
+        let mut edges = HashSet::with_capacity(size * neighbours / 2);
+        for a in 0..size {
+            for offset in 1..=neighbours / 2 {
+                let b = (a + offset) % size;
+                edges.insert((a.min(b), a.max(b)));
+            }
+        }
+
+        // Rewire the far end of each ring edge, avoiding loops and duplicate edges.
+        let ring: Vec<(EntityId, EntityId)> = edges.iter().copied().collect();
+        for (a, b) in ring {
+            if rng.gen::<f32>() >= rewiring {
+                continue;
+            }
+
+            let c = rng.gen_range(0..size);
+            let rewired = (a.min(c), a.max(c));
+            if c != a && !edges.contains(&rewired) {
+                edges.remove(&(a, b));
+                edges.insert(rewired);
+            }
+        }
+
+        edges.into_iter().collect()
+    }
+
+    fn barabasi_albert(size: usize, edges: usize, rng: &mut StdRng) -> Vec<(EntityId, EntityId)> {
+        let mut result = Vec::with_capacity(size * edges);
+        // Every entity appears once per edge, so drawing from it prefers entities of high degree.
+        let mut endpoints: Vec<EntityId> = Vec::with_capacity(2 * size * edges);
+
+        // The first entities form a fully connected core.
+        for a in 0..=edges {
+            for b in 0..a {
+                result.push((a, b));
+                endpoints.push(a);
+                endpoints.push(b);
+            }
+        }
+
+        for a in edges + 1..size {
+            let mut targets = HashSet::with_capacity(edges);
+            while targets.len() < edges {
+                targets.insert(*endpoints.choose(rng).unwrap());
+            }
+
+            for b in targets {
+                result.push((a, b));
+                endpoints.push(a);
+                endpoints.push(b);
+            }
+        }
+
+        result
+    }
+
+    /// Returns the neighbours of the given entity.
+    pub fn neighbours(&self, entity_id: EntityId) -> &[EntityId] {
+        self.neighbours
+            .get(entity_id)
+            .map_or(&[], |neighbours| neighbours.as_slice())
+    }
+
+    /// Returns the mean number of neighbours of the initial population.
+    pub fn mean_degree(&self) -> f32 {
+        let edges: usize = self.neighbours.iter().map(Vec::len).sum();
+        edges as f32 / self.neighbours.len().max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn assert_simple(edges: &[(EntityId, EntityId)]) {
+        let mut unique = HashSet::new();
+        for &(a, b) in edges {
+            assert_ne!(a, b, "self-loop at {}", a);
+            assert!(
+                unique.insert((a.min(b), a.max(b))),
+                "duplicate edge {}-{}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn erdos_renyi_has_the_mean_degree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let edges = Network::erdos_renyi(1000, 6.0, &mut rng);
+
+        assert_eq!(edges.len(), 3000);
+        assert_simple(&edges);
+
+        let network = Network::new(&Graph::ErdosRenyi { mean_degree: 6.0 }, 1000, &mut rng);
+        assert!((network.mean_degree() - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn erdos_renyi_is_at_most_complete() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let edges = Network::erdos_renyi(5, 10.0, &mut rng);
+
+        assert_eq!(edges.len(), 10);
+        assert_simple(&edges);
+    }
+
+    #[test]
+    fn watts_strogatz_keeps_the_edge_count() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for rewiring in [0.0, 0.3, 1.0] {
+            let edges = Network::watts_strogatz(1000, 4, rewiring, &mut rng);
+
+            assert_eq!(edges.len(), 2000);
+            assert_simple(&edges);
+        }
+    }
+
+    #[test]
+    fn barabasi_albert_connects_every_entity() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let network = Network::new(&Graph::BarabasiAlbert { edges: 3 }, 1000, &mut rng);
+
+        assert_simple(&Network::barabasi_albert(1000, 3, &mut rng));
+        assert!((0..1000).all(|id| network.neighbours(id).len() >= 3));
+        assert!((network.mean_degree() - 6.0).abs() < 0.1);
+    }
+}
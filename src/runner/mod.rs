@@ -2,6 +2,6 @@ mod nographics;
 mod runner;
 mod sdl;
 
-pub use nographics::NoGraphics;
+pub use nographics::{run_engine, NoGraphics};
 pub use runner::Runner;
 pub use sdl::SDL;
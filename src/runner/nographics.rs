@@ -1,12 +1,62 @@
 use crate::{
+    config::PopulationFile,
+    engine::Engine,
     simulator::Simulator,
-    statistics::{DataFrame, Demographics},
+    statistics::{DataFrame, Demographics, OffspringDistribution, Outcomes},
     util::print_progress,
     CONFIG,
 };
 
 use super::Runner;
 
+/// Runs the engine until its time limit, collecting the data of every tick.
+fn simulate(engine: &mut impl Engine, show_progress: bool) -> DataFrame {
+    let mut dataframe = DataFrame::new(CONFIG.core.time_limit as usize + 1);
+    engine.push_data(&mut dataframe);
+
+    while !engine.done() {
+        if show_progress {
+            let progress = engine.current_time() as f32 / CONFIG.core.time_limit as f32 * 100.0;
+            print_progress(progress);
+            print!("\r");
+        }
+
+        engine.step();
+
+        engine.push_data(&mut dataframe);
+    }
+
+    dataframe
+}
+
+/// Runs an engine other than the spatial simulator, which only reports the compartments.
+/// The reference is exported alongside, e.g. the solution of the compartmental model.
+pub fn run_engine(
+    engine: &mut impl Engine,
+    reference: Option<DataFrame>,
+    debug: bool,
+    show_progress: bool,
+    export: bool,
+) {
+    let dataframe = simulate(engine, show_progress);
+
+    if debug {
+        println!("{}", dataframe);
+    }
+
+    if export {
+        match std::fs::create_dir_all(format!("export/{}", CONFIG.name())) {
+            Ok(_) => {
+                dataframe
+                    .export(reference.as_ref())
+                    .expect("Failed to export dataframe.");
+                CONFIG.core.export().expect("Failed to export core config.");
+            }
+            Err(e) => println!("Failed to create export directory: {}", e),
+        }
+    }
+}
+
 pub struct NoGraphics {
     pub simulator: Simulator,
 }
@@ -17,35 +67,41 @@ impl Runner for NoGraphics {
     }
 
     fn run(&mut self, debug: bool, show_progress: bool, export: bool) {
-        let mut dataframe = DataFrame::new(CONFIG.core.population_size as usize);
-        dataframe.push_data(&self.simulator);
-
-        for i in 0..CONFIG.core.time_limit {
-            if show_progress {
-                let progress = i as f32 / CONFIG.core.time_limit as f32 * 100.0;
-                print_progress(progress);
-                print!("\r");
-            }
-
-            self.simulator.step();
-
-            dataframe.push_data(&self.simulator);
+        if export {
+            PopulationFile::export_run(self.simulator.population().get())
+                .expect("Failed to export population.");
         }
 
+        // The reference starts from the initial population.
+        let reference = CONFIG
+            .core
+            .compartmental
+            .as_ref()
+            .map(|model| model.solve(self.simulator.population().get()));
+        let dataframe = simulate(&mut self.simulator, show_progress);
+
         let demographics = Demographics::from_simulator(&self.simulator);
+        let outcomes = Outcomes::from_simulator(&self.simulator);
+        let offspring = OffspringDistribution::from_simulator(&self.simulator);
 
         if debug {
             println!("{}", dataframe);
             println!("{}", demographics);
+            println!("{}", outcomes);
+            println!("{}", offspring);
         }
 
         if export {
             match std::fs::create_dir_all(format!("export/{}", CONFIG.name())) {
                 Ok(_) => {
-                    dataframe.export().expect("Failed to export dataframe.");
+                    dataframe
+                        .export(reference.as_ref())
+                        .expect("Failed to export dataframe.");
                     demographics
                         .export()
                         .expect("Failed to export demographics.");
+                    outcomes.export().expect("Failed to export outcomes.");
+                    offspring.export().expect("Failed to export offspring.");
                     CONFIG.core.export().expect("Failed to export core config.");
                 }
                 Err(e) => {
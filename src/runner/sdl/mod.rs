@@ -2,9 +2,12 @@ use quadtree::Positioned;
 use rusty_gl::{color, shapes::CustomShape2D, vertices::Vertex};
 
 use crate::{
+    config::PopulationFile,
+    engine::Engine,
     entity::{Entity, InfectionStatus},
+    hospital::Hospital,
     simulator::Simulator,
-    statistics::{DataFrame, Demographics},
+    statistics::{DataFrame, Demographics, OffspringDistribution, Outcomes},
     util::print_progress,
     CONFIG,
 };
@@ -36,6 +39,36 @@ fn entity_to_vertex(entity: &Entity) -> Vertex {
     )
 }
 
+/// Size of the squares marking the hospitals.
+const HOSPITAL_SIZE: f32 = 6.0;
+
+/// Maps a hospital to the two triangles of a square, colored by its occupancy.
+/// Empty hospitals are blue, full ones magenta.
+fn hospital_to_vertices(hospital: &Hospital) -> Vec<Vertex> {
+    let pos = hospital.position();
+    let half = HOSPITAL_SIZE * 0.5;
+    let load = (hospital.count() as f32 / hospital.beds().max(1) as f32).clamp(0.0, 1.0);
+    let color = [load, 0.0, 1.0];
+
+    [
+        (-half, -half),
+        (half, -half),
+        (half, half),
+        (-half, -half),
+        (half, half),
+        (-half, half),
+    ]
+    .iter()
+    .map(|(x, y)| {
+        Vertex::new(
+            [pos.x + x, pos.y + y, 0.0].into(),
+            color.into(),
+            [0.0, 0.0].into(),
+        )
+    })
+    .collect()
+}
+
 impl Runner for SDL {
     fn new(simulator: Simulator) -> SDL {
         SDL { simulator }
@@ -61,8 +94,19 @@ impl Runner for SDL {
             rusty_gl::debug::enable();
         }
 
+        if export {
+            PopulationFile::export_run(self.simulator.population().get())
+                .expect("Failed to export population.");
+        }
+
+        // The reference starts from the initial population.
+        let reference = CONFIG
+            .core
+            .compartmental
+            .as_ref()
+            .map(|model| model.solve(self.simulator.population().get()));
         let mut dataframe = DataFrame::new(CONFIG.core.population_size as usize);
-        dataframe.push_data(&self.simulator);
+        self.simulator.push_data(&mut dataframe);
 
         'main: loop {
             for event in event_pump.poll_iter() {
@@ -92,7 +136,18 @@ impl Runner for SDL {
             let shape = CustomShape2D::new(vertices, gl::POINTS);
             window.draw(&shape);
 
-            dataframe.push_data(&self.simulator);
+            let hospitals: Vec<Vertex> = self
+                .simulator
+                .health_system()
+                .lock()
+                .unwrap()
+                .hospitals()
+                .iter()
+                .flat_map(hospital_to_vertices)
+                .collect();
+            window.draw(&CustomShape2D::new(hospitals, gl::TRIANGLES));
+
+            self.simulator.push_data(&mut dataframe);
             window.gl_swap();
 
             if self.simulator.done() {
@@ -101,19 +156,27 @@ impl Runner for SDL {
         }
 
         let demographics = Demographics::from_simulator(&self.simulator);
+        let outcomes = Outcomes::from_simulator(&self.simulator);
+        let offspring = OffspringDistribution::from_simulator(&self.simulator);
 
         if debug {
             println!("{}", dataframe);
             println!("{}", demographics);
+            println!("{}", outcomes);
+            println!("{}", offspring);
         }
 
         if export {
             match std::fs::create_dir_all(format!("export/{}", CONFIG.name())) {
                 Ok(_) => {
-                    dataframe.export().expect("Failed to export dataframe.");
+                    dataframe
+                        .export(reference.as_ref())
+                        .expect("Failed to export dataframe.");
                     demographics
                         .export()
                         .expect("Failed to export demographics.");
+                    outcomes.export().expect("Failed to export outcomes.");
+                    offspring.export().expect("Failed to export offspring.");
                     CONFIG.core.export().expect("Failed to export core config.");
                 }
                 Err(e) => {
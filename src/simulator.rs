@@ -5,12 +5,25 @@ use std::{
 
 use crossbeam::thread;
 use quadtree::{Positioned, Quadtree, Rectangle};
-use rand::{prelude::StdRng, Rng, SeedableRng};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    prelude::StdRng,
+    seq::SliceRandom,
+    SeedableRng,
+};
 
 use crate::{
-    entity::{Entity, InfectionStatus},
-    hospital::Hospital,
-    util::UnsafeArray,
+    config::{ImportationMode, Vitals},
+    engine::Engine,
+    entity::{Entity, EntityId, InfectionStatus},
+    hospital::{Admission, HealthSystem},
+    interventions::{Lockdown, Masks},
+    movement::MovementModel,
+    network::Network,
+    statistics::DataFrame,
+    testing::Testing,
+    transmissions::Transmissions,
+    util::{sample_poisson, UnsafeArray},
     CONFIG,
 };
 
@@ -19,9 +32,14 @@ pub struct Simulator {
     time: u32,
 
     threads: u32,
-    entities_per_thread: u32,
 
-    hospital: Mutex<Hospital>,
+    health_system: Mutex<HealthSystem>,
+    testing: Testing,
+    transmissions: Transmissions,
+    network: Option<Network>, // Contacts along the edges of a graph instead of within the infection radius.
+    movement: Box<dyn MovementModel>,
+    births: u32,   // Total number of entities born during the simulation.
+    imported: u32, // Total number of cases imported from outside the simulated area.
 
     delta_time: f32,
     frame_timer: Instant,
@@ -31,26 +49,69 @@ pub struct Simulator {
 
 impl Simulator {
     pub fn new(threads: u32) -> Simulator {
-        let population = (0..CONFIG.core.population_size)
-            .map(|_| Entity::new())
-            .collect();
+        let mut rng = StdRng::from_entropy();
+        let mut population = Simulator::initial_population(&mut rng);
+
+        let mut health_system = HealthSystem::new(CONFIG.core.admission_policy.clone());
+        health_system.assign_staff(&mut population);
 
-        let entities_per_thread = CONFIG.core.population_size / threads;
+        let testing = Testing::new(CONFIG.core.testing.clone(), &population);
+
+        let network = CONFIG.core.contact_network.as_ref().map(|contact_network| {
+            Network::new(&contact_network.graph, population.len(), &mut rng)
+        });
+
+        let mut transmissions = Transmissions::new(population.len());
+        for entity in &population {
+            if matches!(entity.status(), InfectionStatus::Infected(_)) {
+                transmissions.record(entity.id(), None, 0);
+            }
+        }
 
         Simulator {
             population: Arc::new(UnsafeArray::new(population)),
             time: 0,
             threads,
-            entities_per_thread,
-            hospital: Mutex::new(Hospital::new(CONFIG.core.hospital_capacity as usize)),
+            health_system: Mutex::new(health_system),
+            testing,
+            transmissions,
+            network,
+            movement: CONFIG.core.movement.model(),
+            births: 0,
+            imported: 0,
             delta_time: 1.0,
             frame_timer: Instant::now(),
-            rng: StdRng::from_entropy(),
+            rng,
+        }
+    }
+
+    /// Creates the initial population, from the population file or sampled,
+    /// and applies the initial conditions.
+    pub fn initial_population(rng: &mut StdRng) -> Vec<Entity> {
+        let mut population: Vec<Entity> = match CONFIG.population_entries() {
+            Some(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(id, entry)| Entity::from_entry(id, entry))
+                .collect(),
+            None => (0..CONFIG.core.population_size as usize)
+                .map(Entity::new)
+                .collect(),
+        };
+
+        if let Some(initial_conditions) = &CONFIG.core.initial_conditions {
+            initial_conditions.apply(&mut population, rng);
         }
+
+        population
     }
 
     /// Helper function for iterating over the population in parallel.
     fn for_each_entity(&self, f: &(impl Fn(&mut Entity) + Send + Sync)) {
+        // The population grows through births, so the chunks are computed anew every time
+        let population_size = self.population.get().len() as u32;
+        let entities_per_thread = population_size / self.threads;
+
         thread::scope(|scope| {
             let mut thread_handles = Vec::new();
 
@@ -58,11 +119,11 @@ impl Simulator {
                 let population = self.population.clone();
 
                 let handle = scope.spawn(move |_| {
-                    let start_index = thread_index * self.entities_per_thread;
-                    let end_index = (thread_index + 1) * self.entities_per_thread
+                    let start_index = thread_index * entities_per_thread;
+                    let end_index = (thread_index + 1) * entities_per_thread
                         + if thread_index == self.threads - 1 {
                             // If the population size isnt evenly divisible by the number of threads, the last thread will have a bigger chunk
-                            CONFIG.core.population_size % self.threads
+                            population_size % self.threads
                         } else {
                             0
                         };
@@ -82,8 +143,124 @@ impl Simulator {
         .unwrap();
     }
 
+    /// Adds the newborns of this tick to the population.
+    /// Their number is Poisson distributed around the birth rate of the living population.
+    fn give_births(&mut self, vitals: &Vitals) {
+        let alive = self
+            .population
+            .get()
+            .iter()
+            .filter(|entity| !entity.is_dead())
+            .count();
+
+        let expected = vitals.birth_rate / 1000.0 * alive as f32 / CONFIG.ticks_per_year() as f32;
+        let births = sample_poisson(expected as f64, &mut self.rng) as usize;
+
+        let population = Arc::get_mut(&mut self.population)
+            .expect("The population must not be shared while entities are born.");
+
+        for _ in 0..births {
+            let id = population.get().len();
+            population.push(Entity::newborn(id, vitals, self.time));
+        }
+
+        self.births += births as u32;
+    }
+
+    /// Imports the cases arriving from outside the simulated area this tick.
+    /// Their number is Poisson distributed around the rate of each importation.
+    fn import_cases(&mut self) {
+        for importation in &CONFIG.core.importations {
+            let cases =
+                sample_poisson(importation.rate_at(self.time) as f64, &mut self.rng) as usize;
+            if cases == 0 {
+                continue;
+            }
+
+            let strain = importation
+                .strain
+                .as_ref()
+                .and_then(|name| CONFIG.strain_index(name));
+
+            match importation.mode {
+                ImportationMode::Infect => {
+                    let candidates: Vec<EntityId> = self
+                        .population
+                        .get()
+                        .iter()
+                        .filter(|entity| {
+                            *entity.status() == InfectionStatus::Susceptible
+                                && !entity.is_hospitalized()
+                                && importation.location.contains(entity.position())
+                        })
+                        .map(|entity| entity.id())
+                        .collect();
+
+                    for entity_id in candidates.choose_multiple(&mut self.rng, cases) {
+                        self.entity_mut(*entity_id).infect(strain);
+                        self.transmissions.record(*entity_id, None, self.time);
+                        self.imported += 1;
+                    }
+                }
+                ImportationMode::Traveler => {
+                    let population = Arc::get_mut(&mut self.population)
+                        .expect("The population must not be shared while travelers arrive.");
+
+                    for _ in 0..cases {
+                        // Travelers resemble a random resident.
+                        let resident = population.get().choose(&mut self.rng).unwrap();
+                        let (age, sex) = (resident.age(), resident.sex());
+                        let position = importation.location.sample(&mut self.rng);
+
+                        let id = population.get().len();
+                        population.push(Entity::traveler(id, age, sex, position, strain));
+                        self.transmissions.record(id, None, self.time);
+                        self.imported += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the total number of entities born during the simulation.
+    pub fn births(&self) -> u32 {
+        self.births
+    }
+
+    /// Returns the total number of cases imported from outside the simulated area.
+    pub fn imported(&self) -> u32 {
+        self.imported
+    }
+
+    pub fn population(&self) -> &UnsafeArray<Entity> {
+        &self.population
+    }
+
+    /// Looks up an entity by its id, e.g. the entity of a hospital patient.
+    pub fn entity(&self, id: EntityId) -> &Entity {
+        &self.population.get()[id]
+    }
+
+    fn entity_mut(&self, id: EntityId) -> &mut Entity {
+        self.population.get_at_mut(id)
+    }
+
+    pub fn health_system(&self) -> &Mutex<HealthSystem> {
+        &self.health_system
+    }
+
+    pub fn testing(&self) -> &Testing {
+        &self.testing
+    }
+
+    pub fn transmissions(&self) -> &Transmissions {
+        &self.transmissions
+    }
+}
+
+impl Engine for Simulator {
     /// Performs a single simulation time step.
-    pub fn step(&mut self) {
+    fn step(&mut self) {
         self.delta_time = self.frame_timer.elapsed().as_secs_f32();
         self.frame_timer = Instant::now();
 
@@ -104,6 +281,11 @@ impl Simulator {
             qtree.insert(entity).unwrap();
         }
 
+        // New infections and their infectors, recorded once all entities are updated.
+        let infections = Mutex::new(Vec::new());
+        let masks = Masks::active(self.time);
+        let season = CONFIG.seasonal_factor(self.time);
+
         self.for_each_entity(&|entity: &mut Entity| {
             if entity.is_dead() {
                 return;
@@ -112,74 +294,233 @@ impl Simulator {
             let pos = *entity.position();
 
             if !entity.is_hospitalized() {
-                let range = qtree.query(&Rectangle::new(
-                    pos.x,
-                    pos.y,
-                    CONFIG.core.infection_radius as f32,
-                    CONFIG.core.infection_radius as f32,
-                ));
-
-                // Apply repulsion force, simulates distancing from other entities
-                for other in range {
-                    let diff = pos - *other.position();
-                    entity.apply_force(diff * 0.05 * self.delta_time);
-
-                    // Only check if other entity is infected and entity itself is susceptible
-                    match (other.status(), entity.status()) {
-                        (InfectionStatus::Infected(_), InfectionStatus::Susceptible) => {
-                            if entity.rand() > (CONFIG.infection_chance)(other, entity) {
-                                entity.infect();
+                // Total hazard of all infectious contacts.
+                // Each contact adds -ln(1 - p), so the chance of being infected this tick is
+                // 1 - e^(-hazard) = 1 - (1 - p1)(1 - p2)..., regardless of how many contacts there are.
+                let mut hazard = 0.0;
+                let mut contacts = Vec::new();
+                let susceptible = *entity.status() == InfectionStatus::Susceptible;
+
+                let mut expose = |other: &Entity, receiver: &Entity, chance: f32| {
+                    let chance = chance
+                        * masks.map_or(1.0, |masks| masks.protection(other, receiver))
+                        * season;
+                    let contact_hazard = -(1.0 - chance).max(f32::MIN_POSITIVE).ln();
+                    hazard += contact_hazard;
+                    contacts.push((other.id(), other.strain(), contact_hazard));
+                };
+
+                match (&self.network, &CONFIG.core.contact_network) {
+                    (Some(network), Some(contact_network)) => {
+                        // Hospitalized neighbours are exposed inside their hospital instead.
+                        let neighbours = network
+                            .neighbours(entity.id())
+                            .iter()
+                            .map(|neighbour| &p.get()[*neighbour])
+                            .filter(|neighbour| !neighbour.is_hospitalized());
+
+                        for other in neighbours {
+                            if susceptible && matches!(other.status(), InfectionStatus::Infected(_))
+                            {
+                                let chance = (contact_network.transmission_chance
+                                    * other.infectiousness())
+                                .clamp(0.0, 1.0);
+                                expose(other, entity, chance);
+                            }
+                        }
+                    }
+                    _ => {
+                        let range = qtree.query(&Rectangle::new(
+                            pos.x,
+                            pos.y,
+                            CONFIG.core.infection_radius as f32,
+                            CONFIG.core.infection_radius as f32,
+                        ));
+
+                        // Entities react to each other, e.g. by keeping distance.
+                        for other in range {
+                            self.movement.interact(entity, other, self.delta_time);
+
+                            // Only check if other entity is infected and entity itself is susceptible.
+                            // Hospitalized entities only transmit inside their hospital.
+                            if susceptible
+                                && matches!(other.status(), InfectionStatus::Infected(_))
+                                && !other.is_hospitalized()
+                                && other.position().distance(&pos)
+                                    <= CONFIG.core.infection_radius as f32
+                            {
+                                expose(other, entity, CONFIG.contact_chance(other, entity));
                             }
                         }
-                        _ => {}
                     }
                 }
-            } else {
-                let mut hospital = self.hospital.lock().unwrap();
-                if hospital.ready_to_release(entity) {
-                    hospital.release(entity);
+
+                if hazard > 0.0 && entity.rand() < 1.0 - (-hazard).exp() {
+                    // The infector is drawn by the share of each contact in the total hazard.
+                    // Its strain is passed on.
+                    let index = WeightedIndex::new(
+                        contacts
+                            .iter()
+                            .map(|(_, _, contact_hazard)| *contact_hazard),
+                    )
+                    .unwrap()
+                    .sample(entity.rng());
+                    let (infector, strain, _) = contacts[index];
+
+                    entity.infect(strain);
+                    infections
+                        .lock()
+                        .unwrap()
+                        .push((entity.id(), Some(infector)));
                 }
             }
         });
 
-        for _ in 0..CONFIG.core.tests_per_time {
-            let entity = self
-                .population
-                .get_at_mut(self.rng.gen_range(0..self.population.len()));
-            let mut hospital = self.hospital.lock().unwrap();
+        for (entity_id, infector) in infections.into_inner().unwrap() {
+            self.transmissions.record(entity_id, infector, self.time);
+        }
+
+        let mut health_system = self.health_system.lock().unwrap();
+
+        // Absent staff reduces the capacity available to the following admissions.
+        health_system.update_staffing(|entity_id| self.entity(entity_id));
+
+        // Release patients whose time is up. Dead patients free their bed as well.
+        let population = self.population.get();
+        let released: Vec<EntityId> = health_system
+            .patients()
+            .filter_map(|patient| health_system.patient_entity(patient.entity_id(), population))
+            .filter(|entity| health_system.ready_to_release(entity) || entity.is_dead())
+            .map(|entity| entity.id())
+            .collect();
+
+        for entity_id in released {
+            health_system.release(self.entity_mut(entity_id));
+        }
+
+        let mut positives = self.testing.run(&self.population, self.time);
+
+        // Contacts of positive tested entities are tested in the following ticks.
+        // In a contact network, the contacts are the neighbours.
+        if let Some(radius) = self.testing.tracing_radius() {
+            for entity_id in &positives {
+                match &self.network {
+                    Some(network) => self
+                        .testing
+                        .trace(network.neighbours(*entity_id).iter().copied()),
+                    None => {
+                        let pos = *self.entity(*entity_id).position();
+                        let contacts = qtree
+                            .query(&Rectangle::new(pos.x, pos.y, radius, radius))
+                            .into_iter()
+                            .map(|contact| contact.id())
+                            .filter(|contact_id| contact_id != entity_id);
+
+                        self.testing.trace(contacts);
+                    }
+                }
+            }
+        }
+
+        // Positive tested entities are admitted in order of their priority.
+        // The sort is stable, so entities of equal priority keep the order they were tested in.
+        let population = self.population.get();
+        positives.sort_by(|a, b| {
+            health_system
+                .priority(&population[*b])
+                .total_cmp(&health_system.priority(&population[*a]))
+        });
+
+        for index in positives {
+            let entity = self.population.get_at_mut(index);
 
-            if entity.test() {
-                let _ = hospital.try_hospitalize(entity);
+            match health_system.try_hospitalize(entity) {
+                Admission::Preempted(entity_id) => {
+                    let preempted = self.entity_mut(entity_id);
+                    preempted.unset_hospitalized();
+                    preempted.deny_care();
+                }
+                Admission::TurnedAway => entity.deny_care(),
+                _ => {}
             }
         }
 
+        // Severe cases that were not tested would not find a bed either while the hospitals are full.
+        if let Some(overflow) = &CONFIG.core.overflow_mortality {
+            let untreated: Vec<usize> = self
+                .population
+                .get()
+                .iter()
+                .enumerate()
+                .filter(|(_, entity)| {
+                    matches!(entity.status(), InfectionStatus::Infected(_))
+                        && !entity.is_hospitalized()
+                        && entity.severity() >= overflow.min_severity
+                        && !health_system.has_bed_for(entity)
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            for index in untreated {
+                self.population.get_at_mut(index).deny_care();
+            }
+        }
+
+        // Hospitalized entities are skipped above, they are exposed inside their hospital instead.
+        let nosocomial = health_system.transmit(|entity_id| self.entity_mut(entity_id));
+        for (entity_id, infector) in nosocomial {
+            self.transmissions.record(entity_id, infector, self.time);
+        }
+
+        drop(health_system);
+
+        let lockdown = Lockdown::active(self.time);
+        let vitals = CONFIG.core.vitals.as_ref();
+        let time = self.time;
         self.for_each_entity(&|entity: &mut Entity| {
             if entity.is_dead() {
                 return;
             }
 
             entity.update_status();
-            entity.update_movement();
+
+            if let Some(vitals) = vitals {
+                entity.update_vitals(vitals, time);
+                if entity.is_dead() {
+                    return;
+                }
+            }
+
+            entity.update_movement(&*self.movement, lockdown);
         });
 
-        self.hospital.lock().unwrap().update();
+        self.health_system
+            .lock()
+            .unwrap()
+            .update(|entity_id| self.entity_mut(entity_id));
+
+        // The quadtree borrows the population, which has to be released before it can grow.
+        drop(qtree);
+        drop(p);
+
+        if let Some(vitals) = vitals {
+            self.give_births(vitals);
+        }
+
+        self.import_cases();
 
         self.time += 1;
     }
 
-    pub fn done(&self) -> bool {
+    fn done(&self) -> bool {
         self.time >= CONFIG.core.time_limit
     }
 
-    pub fn current_time(&self) -> u32 {
+    fn current_time(&self) -> u32 {
         self.time
     }
 
-    pub fn population(&self) -> &UnsafeArray<Entity> {
-        &self.population
-    }
-
-    pub fn hospital(&self) -> &Mutex<Hospital> {
-        &self.hospital
+    fn push_data(&self, dataframe: &mut DataFrame) {
+        dataframe.push_data(self)
     }
 }
@@ -1,53 +1,87 @@
 use std::fmt::Display;
 
+use crate::testing::TestCounts;
+
 /// A data point is a all the given statistical information for a single simulation time step.
 pub struct DataPoint {
     pub(super) timestamp: u32,
+    pub(super) date: String, // Simulated date, as YYYY-MM-DD.
     pub(super) susceptible: u32,
     pub(super) infected: u32,
     pub(super) hospitalized: u32,
+    pub(super) ward: u32,
+    pub(super) icu: u32,
+    pub(super) ventilator: u32,
+    pub(super) hospitals: Vec<u32>, // Occupancy of each hospital.
     pub(super) recovered: u32,
-    pub(super) dead: u32,
+    pub(super) dead: u32,                    // Deaths caused by the disease.
+    pub(super) dead_care_not_available: u32, // Subset of dead, attributed to a full hospital.
+    pub(super) dead_natural: u32,            // Deaths of natural causes, not included in dead.
+    pub(super) births: u32,                  // Cumulative count of newborns.
+    pub(super) imported: u32, // Cumulative count of cases imported from outside the simulated area.
+    pub(super) not_eligible: u32, // Cumulative count of positive tested entities rejected by the admission policy.
+    pub(super) turned_away: u32, // Cumulative count of eligible entities rejected by a full hospital.
+    pub(super) preempted: u32, // Cumulative count of patients released early for higher priority entities.
+    pub(super) transfers: u32, // Cumulative count of patients transferred between hospitals.
+    pub(super) nosocomial: u32, // Cumulative count of infections acquired inside a hospital.
+    pub(super) staff_absent: u32, // Health-care workers absent due to illness or death.
+    pub(super) tests: TestCounts, // Tests performed during this time step.
+    pub(super) masks: bool,    // Whether masks were in effect during this time step.
+    pub(super) lockdown: bool, // Whether a lockdown was in effect during this time step.
+    pub(super) season: f32,    // Seasonal multiplier on the chance of infection.
+    pub(super) strains: Vec<u32>, // Infected entities of each configured strain, the base strain excluded.
 }
 
 impl Display for DataPoint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "[Timestamp: {}, Susceptible: {}, Infected: {}, Hospitalized: {}, Recovered: {}, Dead: {}]\n",
-            self.timestamp, self.susceptible, self.infected, self.hospitalized, self.recovered, self.dead
+            "[Timestamp: {} ({}), Susceptible: {}, Infected: {}, Hospitalized: {} (Ward: {}, ICU: {}, Ventilator: {}, Hospitals: {:?}), Recovered: {}, Dead: {} (Care not available: {}), Natural deaths: {}, Births: {}, Imported: {}, Not eligible: {}, Turned away: {}, Preempted: {}, Transfers: {}, Nosocomial: {}, Staff absent: {}, Tests: {} (Positives: {}, TP: {}, FP: {}, TN: {}, FN: {}, Cost: {}), Masks: {}, Lockdown: {}, Season: {}, Strains: {:?}]\n",
+            self.timestamp, self.date, self.susceptible, self.infected, self.hospitalized, self.ward, self.icu, self.ventilator, self.hospitals, self.recovered, self.dead, self.dead_care_not_available, self.dead_natural, self.births, self.imported, self.not_eligible, self.turned_away, self.preempted, self.transfers, self.nosocomial, self.staff_absent, self.tests.tests, self.tests.positives, self.tests.true_positives, self.tests.false_positives, self.tests.true_negatives, self.tests.false_negatives, self.tests.cost, self.masks, self.lockdown, self.season, self.strains
         )
     }
 }
 
 impl DataPoint {
-    pub fn new(
-        timestamp: u32,
-        susceptible: u32,
-        infected: u32,
-        hospitalized: u32,
-        recovered: u32,
-        dead: u32,
-    ) -> DataPoint {
-        DataPoint {
-            timestamp,
-            susceptible,
-            infected,
-            hospitalized,
-            recovered,
-            dead,
-        }
-    }
-
     pub fn as_csv(&self) -> String {
+        let hospitals: Vec<String> = self.hospitals.iter().map(|h| h.to_string()).collect();
+        // Strain columns are appended, as there are none unless strains are configured.
+        let strains: String = self.strains.iter().map(|s| format!(",{}", s)).collect();
+
         format!(
-            "{},{},{},{},{},{}\n",
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}{}\n",
             self.timestamp,
+            self.date,
             self.susceptible,
             self.infected,
             self.hospitalized,
+            self.ward,
+            self.icu,
+            self.ventilator,
+            hospitals.join(","),
             self.recovered,
-            self.dead
+            self.dead,
+            self.dead_care_not_available,
+            self.dead_natural,
+            self.births,
+            self.imported,
+            self.not_eligible,
+            self.turned_away,
+            self.preempted,
+            self.transfers,
+            self.nosocomial,
+            self.staff_absent,
+            self.tests.tests,
+            self.tests.positives,
+            self.tests.true_positives,
+            self.tests.false_positives,
+            self.tests.true_negatives,
+            self.tests.false_negatives,
+            self.tests.cost,
+            self.masks as u8,
+            self.lockdown as u8,
+            self.season,
+            strains
         )
     }
 }
@@ -1,12 +1,18 @@
 use std::{fmt::Display, io::Write};
 
 use plotters::{
-    prelude::{BitMapBackend, ChartBuilder, IntoDrawingArea, LineSeries, PathElement},
+    prelude::{
+        BitMapBackend, ChartBuilder, DashedLineSeries, IntoDrawingArea, LineSeries, PathElement,
+    },
     style::{full_palette::GREY, Color, IntoFont, BLACK, GREEN, MAGENTA, RED, WHITE},
 };
 
-use crate::entity::InfectionStatus;
+use crate::engine::Engine;
+use crate::entity::{DeathCause, InfectionStatus};
+use crate::hospital::CareTier;
+use crate::interventions::{Lockdown, Masks};
 pub use crate::simulator::Simulator;
+use crate::testing::TestCounts;
 use crate::CONFIG;
 
 use super::DataPoint;
@@ -41,6 +47,24 @@ macro_rules! add_chart_line {
     };
 }
 
+/// Like add_chart_line, but dashed and labeled as a reference.
+macro_rules! add_dashed_chart_line {
+    ($chart:expr, $dataframe:expr, $attribute:tt , $color:expr) => {
+        $chart
+            .draw_series(DashedLineSeries::new(
+                $dataframe
+                    .datapoints()
+                    .iter()
+                    .map(|dp| (dp.timestamp, dp.$attribute)),
+                10,
+                5,
+                $color.into(),
+            ))?
+            .label(concat!(stringify!($attribute), " (reference)"))
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], $color));
+    };
+}
+
 impl DataFrame {
     pub fn new(size: usize) -> DataFrame {
         DataFrame {
@@ -57,32 +81,162 @@ impl DataFrame {
         let mut infected = 0 as u32;
         let mut recovered = 0 as u32;
         let mut dead = 0 as u32;
+        let mut dead_care_not_available = 0 as u32;
+        let mut dead_natural = 0 as u32;
+        let mut strains = vec![0 as u32; CONFIG.core.strains.len()];
 
         for entity in simulator.population().get() {
             match entity.health() {
                 InfectionStatus::Susceptible => susceptible += 1,
-                InfectionStatus::Infected(_) => infected += 1,
+                InfectionStatus::Infected(_) => {
+                    infected += 1;
+                    if let Some(strain) = entity.strain() {
+                        strains[strain] += 1;
+                    }
+                }
                 InfectionStatus::Recovered(_) => recovered += 1,
-                InfectionStatus::Dead => dead += 1,
+                InfectionStatus::Dead => match entity.death_cause() {
+                    Some(DeathCause::Natural) => dead_natural += 1,
+                    Some(DeathCause::CareNotAvailable) => {
+                        dead += 1;
+                        dead_care_not_available += 1;
+                    }
+                    _ => dead += 1,
+                },
             }
         }
 
-        self.datapoints.push(DataPoint::new(
-            simulator.current_time(),
+        let health_system = simulator.health_system().lock().unwrap();
+
+        // The interventions of the time step that was just simulated.
+        let time = simulator.current_time().saturating_sub(1);
+
+        self.datapoints.push(DataPoint {
+            timestamp: simulator.current_time(),
+            date: CONFIG.date(simulator.current_time()).to_string(),
             susceptible,
             infected,
-            simulator.hospital().lock().unwrap().count() as u32,
+            hospitalized: health_system.count() as u32,
+            ward: health_system.occupancy(CareTier::Ward) as u32,
+            icu: health_system.occupancy(CareTier::Icu) as u32,
+            ventilator: health_system.occupancy(CareTier::Ventilator) as u32,
+            hospitals: health_system
+                .hospitals()
+                .iter()
+                .map(|hospital| hospital.count() as u32)
+                .collect(),
             recovered,
             dead,
-        ));
+            dead_care_not_available,
+            dead_natural,
+            births: simulator.births(),
+            imported: simulator.imported(),
+            not_eligible: health_system.not_eligible(),
+            turned_away: health_system.turned_away(),
+            preempted: health_system.preempted(),
+            transfers: health_system.transfers(),
+            nosocomial: health_system.nosocomial(),
+            staff_absent: health_system.staff_absent(),
+            tests: simulator.testing().counts(),
+            masks: Masks::active(time).is_some(),
+            lockdown: Lockdown::active(time).is_some(),
+            season: CONFIG.seasonal_factor(time),
+            strains,
+        });
     }
 
-    fn save_as_csv(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Adds a data point holding only the compartments, e.g. of a compartmental model.
+    /// The interventions and seasonality of the configuration are included.
+    pub fn push_compartments(
+        &mut self,
+        timestamp: u32,
+        susceptible: u32,
+        infected: u32,
+        recovered: u32,
+        dead: u32,
+    ) {
+        let time = timestamp.saturating_sub(1);
+
+        self.datapoints.push(DataPoint {
+            timestamp,
+            date: CONFIG.date(timestamp).to_string(),
+            susceptible,
+            infected,
+            hospitalized: 0,
+            ward: 0,
+            icu: 0,
+            ventilator: 0,
+            hospitals: vec![0; CONFIG.core.hospitals.len().max(1)],
+            recovered,
+            dead,
+            dead_care_not_available: 0,
+            dead_natural: 0,
+            births: 0,
+            imported: 0,
+            not_eligible: 0,
+            turned_away: 0,
+            preempted: 0,
+            transfers: 0,
+            nosocomial: 0,
+            staff_absent: 0,
+            tests: TestCounts::default(),
+            masks: Masks::active(time).is_some(),
+            lockdown: Lockdown::active(time).is_some(),
+            season: CONFIG.seasonal_factor(time),
+            strains: vec![0; CONFIG.core.strains.len()],
+        });
+    }
+
+    fn save_as_csv(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // One occupancy column per hospital
+        let hospital_count = self.datapoints.first().map_or(0, |dp| dp.hospitals.len());
+        let hospitals: Vec<String> = (0..hospital_count)
+            .map(|index| format!("hospital_{}", index))
+            .collect();
+        // One column of infected entities per configured strain
+        let strains: String = CONFIG
+            .core
+            .strains
+            .iter()
+            .map(|strain| format!(",infected_{}", strain.name))
+            .collect();
+
         let mut csv = String::new();
         csv.push_str(
             format!(
-                "{},{},{},{},{},{}\n",
-                "time", "susceptible", "infected", "hospital", "recovered", "dead"
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}{}\n",
+                "time",
+                "date",
+                "susceptible",
+                "infected",
+                "hospital",
+                "ward",
+                "icu",
+                "ventilator",
+                hospitals.join(","),
+                "recovered",
+                "dead",
+                "dead_care_not_available",
+                "dead_natural",
+                "births",
+                "imported",
+                "not_eligible",
+                "turned_away",
+                "preempted",
+                "transfers",
+                "nosocomial",
+                "staff_absent",
+                "tests",
+                "positives",
+                "true_positives",
+                "false_positives",
+                "true_negatives",
+                "false_negatives",
+                "test_cost",
+                "masks",
+                "lockdown",
+                "season",
+                strains
             )
             .as_str(),
         );
@@ -91,14 +245,28 @@ impl DataFrame {
             csv.push_str(&format!("{}", datapoint.as_csv()));
         }
 
-        let mut file = std::fs::File::create(format!("export/{}/data.csv", CONFIG.name()))?;
+        let mut file = std::fs::File::create(format!("export/{}/{}", CONFIG.name(), filename))?;
 
         file.write_all(csv.as_bytes())?;
 
         Ok(())
     }
 
-    fn save_as_chart(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Returns the largest number of entities at any time, which grows with births.
+    fn max_population(&self) -> u32 {
+        self.datapoints
+            .iter()
+            .map(|dp| dp.susceptible + dp.infected + dp.recovered + dp.dead + dp.dead_natural)
+            .max()
+            .unwrap_or(0)
+            .max(CONFIG.core.population_size)
+    }
+
+    /// Draws the trend, overlaid with the compartments of the reference as dashed lines if given.
+    fn save_as_chart(
+        &self,
+        reference: Option<&DataFrame>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         let filename = format!("export/{}/trend.png", CONFIG.name());
 
         let root = BitMapBackend::new(&filename, (1000, 1000)).into_drawing_area();
@@ -109,7 +277,7 @@ impl DataFrame {
             .margin(5)
             .x_label_area_size(100)
             .y_label_area_size(100)
-            .build_cartesian_2d(0..CONFIG.core.time_limit, 0..CONFIG.core.population_size)?;
+            .build_cartesian_2d(0..CONFIG.core.time_limit, 0..self.max_population())?;
 
         chart
             .configure_mesh()
@@ -123,6 +291,13 @@ impl DataFrame {
         add_chart_line!(chart, self, recovered, GREEN);
         add_chart_line!(chart, self, dead, BLACK);
 
+        if let Some(reference) = reference {
+            add_dashed_chart_line!(chart, reference, susceptible, GREY);
+            add_dashed_chart_line!(chart, reference, infected, RED);
+            add_dashed_chart_line!(chart, reference, recovered, GREEN);
+            add_dashed_chart_line!(chart, reference, dead, BLACK);
+        }
+
         chart
             .configure_series_labels()
             .background_style(&WHITE.mix(0.8))
@@ -135,9 +310,14 @@ impl DataFrame {
         Ok(())
     }
 
-    pub fn export(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.save_as_csv()?;
-        self.save_as_chart()?;
+    /// Exports the data and the trend chart. A reference, e.g. the solution of a compartmental model,
+    /// is exported as reference.csv and overlaid on the chart.
+    pub fn export(&self, reference: Option<&DataFrame>) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_as_csv("data.csv")?;
+        if let Some(reference) = reference {
+            reference.save_as_csv("reference.csv")?;
+        }
+        self.save_as_chart(reference)?;
 
         Ok(())
     }
@@ -4,16 +4,23 @@ use plotters::{
     prelude::{
         BitMapBackend, ChartBuilder, IntoDrawingArea, IntoSegmentedCoord, Rectangle, SegmentValue,
     },
-    style::{Color, IntoFont, RED, WHITE},
+    style::{Color, IntoFont, BLACK, BLUE, RED, WHITE},
 };
 
-use crate::CONFIG;
+use crate::{entity::Sex, CONFIG};
 
 use super::dataframe::Simulator;
 
 struct Bucket {
     age: u8,
-    count: u32,
+    male: u32,
+    female: u32,
+}
+
+impl Bucket {
+    fn count(&self) -> u32 {
+        self.male + self.female
+    }
 }
 
 pub struct Demographics {
@@ -23,7 +30,14 @@ pub struct Demographics {
 impl Display for Demographics {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for bucket in &self.buckets {
-            writeln!(f, "{}: {}", bucket.age, bucket.count)?;
+            writeln!(
+                f,
+                "{}: {} (male: {}, female: {})",
+                bucket.age,
+                bucket.count(),
+                bucket.male,
+                bucket.female
+            )?;
         }
         Ok(())
     }
@@ -36,7 +50,7 @@ impl Demographics {
         };
 
         for entity in simulator.population().get() {
-            demographics.add(entity.age());
+            demographics.add(entity.age(), entity.sex());
         }
 
         demographics.buckets.sort_by(|a, b| a.age.cmp(&b.age));
@@ -44,29 +58,38 @@ impl Demographics {
         demographics
     }
 
-    fn add(&mut self, age: u8) {
-        // Look for an existing bucket
-        for bucket in &mut self.buckets {
-            if bucket.age == age {
-                bucket.count += 1;
-                return;
+    fn add(&mut self, age: u8, sex: Sex) {
+        let bucket = match self.buckets.iter_mut().find(|bucket| bucket.age == age) {
+            Some(bucket) => bucket,
+            None => {
+                // There is no existing bucket, so create a new one.
+                self.buckets.push(Bucket {
+                    age,
+                    male: 0,
+                    female: 0,
+                });
+                self.buckets.last_mut().unwrap()
             }
-        }
+        };
 
-        // There is no existing bucket, so create a new one.
-        self.buckets.push(Bucket { age, count: 1 });
+        match sex {
+            Sex::Male => bucket.male += 1,
+            Sex::Female => bucket.female += 1,
+        }
     }
 
     fn max_bucket(&self) -> u32 {
         let mut max = 0;
         for bucket in &self.buckets {
-            if bucket.count > max {
-                max = bucket.count;
+            if bucket.count() > max {
+                max = bucket.count();
             }
         }
         max
     }
 
+    /// Exports the demographics as a bar chart.
+    /// Each bar is split into its male (bottom) and female (top) part.
     pub fn export(&self) -> Result<(), Box<dyn std::error::Error>> {
         let filename = format!("export/{}/demographics.png", CONFIG.name());
 
@@ -90,15 +113,38 @@ impl Demographics {
             .y_desc("Count")
             .draw()?;
 
-        chart.draw_series(self.buckets.iter().map(|bucket| {
-            Rectangle::new(
-                [
-                    (SegmentValue::Exact(bucket.age as u32), 0),
-                    (SegmentValue::Exact(bucket.age as u32 + 1), bucket.count),
-                ],
-                RED.filled(),
-            )
-        }))?;
+        chart
+            .draw_series(self.buckets.iter().map(|bucket| {
+                Rectangle::new(
+                    [
+                        (SegmentValue::Exact(bucket.age as u32), 0),
+                        (SegmentValue::Exact(bucket.age as u32 + 1), bucket.male),
+                    ],
+                    BLUE.filled(),
+                )
+            }))?
+            .label("male")
+            .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], BLUE.filled()));
+
+        chart
+            .draw_series(self.buckets.iter().map(|bucket| {
+                Rectangle::new(
+                    [
+                        (SegmentValue::Exact(bucket.age as u32), bucket.male),
+                        (SegmentValue::Exact(bucket.age as u32 + 1), bucket.count()),
+                    ],
+                    RED.filled(),
+                )
+            }))?
+            .label("female")
+            .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], RED.filled()));
+
+        chart
+            .configure_series_labels()
+            .background_style(&WHITE.mix(0.8))
+            .border_style(&BLACK)
+            .label_font(("sans-serif", 20).into_font())
+            .draw()?;
 
         root.present()?;
 
@@ -1,7 +1,11 @@
 mod data_point;
 mod dataframe;
 mod demographics;
+mod offspring;
+mod outcomes;
 
 pub use data_point::DataPoint;
 pub use dataframe::DataFrame;
 pub use demographics::Demographics;
+pub use offspring::OffspringDistribution;
+pub use outcomes::Outcomes;
@@ -0,0 +1,159 @@
+use std::{fmt::Display, io::Write};
+
+use crate::{
+    entity::{EntityId, InfectionStatus},
+    transmissions::Transmissions,
+    CONFIG,
+};
+
+use super::dataframe::Simulator;
+
+/// Distribution of the number of secondary infections caused per infection.
+/// Only infections that have ended are considered, as ongoing ones may still cause more.
+pub struct OffspringDistribution {
+    counts: Vec<u32>,              // Number of infections by their number of offspring.
+    mean_generation_interval: f32, // Over all attributed infections, including ongoing ones.
+    superspreaders: f32, // Smallest fraction of the infections that caused 80% of all transmissions.
+}
+
+impl Display for OffspringDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (mean, variance) = self.moments();
+
+        writeln!(
+            f,
+            "Offspring: [Mean: {:.3}, Variance: {:.3}, 80% of transmissions caused by: {:.1}% of infections, Mean generation interval: {:.1}]",
+            mean,
+            variance,
+            self.superspreaders * 100.0,
+            self.mean_generation_interval
+        )?;
+
+        for (offspring, count) in self.counts.iter().enumerate() {
+            writeln!(f, "{}: {}", offspring, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl OffspringDistribution {
+    pub fn from_simulator(simulator: &Simulator) -> OffspringDistribution {
+        let population = simulator.population().get();
+
+        OffspringDistribution::from_transmissions(simulator.transmissions(), |entity| {
+            matches!(population[entity].status(), InfectionStatus::Infected(_))
+        })
+    }
+
+    /// Builds the distribution from the recorded transmissions,
+    /// given which entities are currently infected.
+    fn from_transmissions(
+        transmissions: &Transmissions,
+        infected: impl Fn(EntityId) -> bool,
+    ) -> OffspringDistribution {
+        let mut offspring: Vec<u32> = transmissions
+            .infections()
+            .iter()
+            .enumerate()
+            .filter(|(index, infection)| {
+                !(transmissions.is_latest(*index) && infected(infection.entity))
+            })
+            .map(|(_, infection)| infection.offspring)
+            .collect();
+
+        let mut counts = vec![0; offspring.iter().max().map_or(0, |max| *max as usize + 1)];
+        for value in &offspring {
+            counts[*value as usize] += 1;
+        }
+
+        // Fewest infections needed to account for 80% of the transmissions.
+        offspring.sort_unstable_by(|a, b| b.cmp(a));
+        let total: u32 = offspring.iter().sum();
+        let mut cumulative = 0;
+        let spreaders = offspring
+            .iter()
+            .take_while(|value| {
+                let below = (cumulative as f32) < total as f32 * 0.8;
+                cumulative += **value;
+                below
+            })
+            .count();
+
+        let intervals: Vec<u32> = transmissions
+            .infections()
+            .iter()
+            .filter_map(|infection| infection.generation_interval)
+            .collect();
+
+        OffspringDistribution {
+            counts,
+            mean_generation_interval: intervals.iter().sum::<u32>() as f32
+                / intervals.len().max(1) as f32,
+            superspreaders: spreaders as f32 / offspring.len().max(1) as f32,
+        }
+    }
+
+    /// Returns the mean and variance of the number of offspring.
+    fn moments(&self) -> (f32, f32) {
+        let infections: u32 = self.counts.iter().sum();
+        let infections = infections.max(1) as f32;
+
+        let mean = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(offspring, count)| offspring as f32 * *count as f32)
+            .sum::<f32>()
+            / infections;
+
+        let variance = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(offspring, count)| (offspring as f32 - mean).powi(2) * *count as f32)
+            .sum::<f32>()
+            / infections;
+
+        (mean, variance)
+    }
+
+    pub fn export(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut csv = String::new();
+        csv.push_str("offspring,infections\n");
+
+        for (offspring, count) in self.counts.iter().enumerate() {
+            csv.push_str(&format!("{},{}\n", offspring, count));
+        }
+
+        let mut file = std::fs::File::create(format!("export/{}/offspring.csv", CONFIG.name()))?;
+        file.write_all(csv.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_a_transmission_chain() {
+        let mut transmissions = Transmissions::new(6);
+        transmissions.record(0, None, 0);
+        for (entity, time) in [(1, 2), (2, 3), (3, 3), (4, 5)] {
+            transmissions.record(entity, Some(0), time);
+        }
+        transmissions.record(5, Some(1), 6);
+        // The reinfection of entity 0 is still ongoing and not counted.
+        transmissions.record(0, Some(5), 20);
+
+        let distribution =
+            OffspringDistribution::from_transmissions(&transmissions, |entity| entity == 0);
+
+        assert_eq!(distribution.counts, vec![3, 2, 0, 0, 1]);
+        assert_eq!(distribution.moments().0, 1.0);
+        assert!((distribution.mean_generation_interval - 31.0 / 6.0).abs() < 1e-6);
+        // 4 + 1 of the 6 transmissions are caused by 2 of the 6 infections.
+        assert!((distribution.superspreaders - 2.0 / 6.0).abs() < 1e-6);
+    }
+}
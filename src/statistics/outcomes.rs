@@ -0,0 +1,144 @@
+use std::{fmt::Display, io::Write};
+
+use crate::{
+    entity::{DeathCause, Entity, InfectionStatus, Occupation, Sex},
+    CONFIG,
+};
+
+use super::dataframe::Simulator;
+
+/// Final infection status counts of one group of the population.
+struct OutcomeGroup {
+    name: String,
+    susceptible: u32,
+    infected: u32,
+    recovered: u32,
+    dead: u32,
+    dead_care_not_available: u32, // Subset of dead, attributed to a full hospital.
+    dead_natural: u32,            // Deaths of natural causes, not included in dead.
+}
+
+impl OutcomeGroup {
+    fn new(name: &str) -> OutcomeGroup {
+        OutcomeGroup {
+            name: name.to_string(),
+            susceptible: 0,
+            infected: 0,
+            recovered: 0,
+            dead: 0,
+            dead_care_not_available: 0,
+            dead_natural: 0,
+        }
+    }
+
+    fn add(&mut self, entity: &Entity) {
+        match entity.health() {
+            InfectionStatus::Susceptible => self.susceptible += 1,
+            InfectionStatus::Infected(_) => self.infected += 1,
+            InfectionStatus::Recovered(_) => self.recovered += 1,
+            InfectionStatus::Dead => match entity.death_cause() {
+                Some(DeathCause::Natural) => self.dead_natural += 1,
+                Some(DeathCause::CareNotAvailable) => {
+                    self.dead += 1;
+                    self.dead_care_not_available += 1;
+                }
+                _ => self.dead += 1,
+            },
+        }
+    }
+
+    fn as_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}\n",
+            self.name,
+            self.susceptible,
+            self.infected,
+            self.recovered,
+            self.dead,
+            self.dead_care_not_available,
+            self.dead_natural
+        )
+    }
+}
+
+/// Outcomes summarize the state of the population at the end of a simulation,
+/// split into groups such as the entities sex.
+/// Health-care workers are listed as an additional group if nosocomial transmission is enabled,
+/// as is every configured risk factor.
+pub struct Outcomes {
+    groups: Vec<OutcomeGroup>,
+}
+
+impl Display for Outcomes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for group in &self.groups {
+            writeln!(
+                f,
+                "{}: [Susceptible: {}, Infected: {}, Recovered: {}, Dead: {} (Care not available: {}), Natural deaths: {}]",
+                group.name,
+                group.susceptible,
+                group.infected,
+                group.recovered,
+                group.dead,
+                group.dead_care_not_available,
+                group.dead_natural
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Outcomes {
+    pub fn from_simulator(simulator: &Simulator) -> Outcomes {
+        let mut male = OutcomeGroup::new(Sex::Male.as_str());
+        let mut female = OutcomeGroup::new(Sex::Female.as_str());
+        let mut staff = OutcomeGroup::new(Occupation::HealthCareWorker.as_str());
+        let mut risk_factors: Vec<OutcomeGroup> = CONFIG
+            .core
+            .risk_factors
+            .iter()
+            .map(|risk_factor| OutcomeGroup::new(&risk_factor.name))
+            .collect();
+
+        for entity in simulator.population().get() {
+            match entity.sex() {
+                Sex::Male => male.add(entity),
+                Sex::Female => female.add(entity),
+            }
+
+            if entity.occupation() == Occupation::HealthCareWorker {
+                staff.add(entity);
+            }
+
+            for (index, group) in risk_factors.iter_mut().enumerate() {
+                if entity.has_risk_factor_at(index) {
+                    group.add(entity);
+                }
+            }
+        }
+
+        let mut groups = vec![male, female];
+        if CONFIG.core.nosocomial.is_some() {
+            groups.push(staff);
+        }
+        groups.append(&mut risk_factors);
+
+        Outcomes { groups }
+    }
+
+    pub fn export(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut csv = String::new();
+        csv.push_str(
+            "group,susceptible,infected,recovered,dead,dead_care_not_available,dead_natural\n",
+        );
+
+        for group in &self.groups {
+            csv.push_str(&group.as_csv());
+        }
+
+        let mut file = std::fs::File::create(format!("export/{}/outcomes.csv", CONFIG.name()))?;
+        file.write_all(csv.as_bytes())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+mod strategy;
+mod test_counts;
+mod test_kind;
+mod testing;
+
+pub use strategy::TestingStrategy;
+pub use test_counts::TestCounts;
+pub use test_kind::TestKind;
+pub use testing::Testing;
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entity::Occupation;
+
+/// Determines who is tested each tick.
+/// Every strategy performs at most tests_per_time tests per tick,
+/// dead and hospitalized entities are never tested.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum TestingStrategy {
+    #[default]
+    Random, // Entities drawn uniformly at random.
+    Symptomatic, // Only entities showing symptoms.
+    /// Entities within radius of a positive tested entity are tested in the following ticks.
+    /// Random tests fill up the remaining tests.
+    Contacts {
+        radius: f32,
+    },
+    /// Entities within the age range and of the occupation, any occupation if None.
+    Targeted {
+        min_age: u8,
+        max_age: u8,
+        occupation: Option<Occupation>,
+    },
+    /// Random pools, the members of positive pools are retested individually.
+    Pooled {
+        pool_size: u32,
+    },
+    /// A fixed random cohort, tested in turns so each member is retested regularly.
+    Cohort {
+        size: u32,
+    },
+}
@@ -0,0 +1,28 @@
+/// Number of tests performed during a single tick, split by their outcome.
+/// A pooled test counts as a single test, which is a true positive if any pool member is infected.
+/// Positives count the positive results taking effect this tick, which were possibly tested ticks earlier.
+#[derive(Clone, Copy, Default)]
+pub struct TestCounts {
+    pub tests: u32,
+    pub positives: u32,
+    pub true_positives: u32,
+    pub false_positives: u32,
+    pub true_negatives: u32,
+    pub false_negatives: u32,
+    pub cost: f32,
+}
+
+impl TestCounts {
+    /// Records the result of a single test.
+    pub fn record(&mut self, infected: bool, positive: bool, cost: f32) {
+        self.tests += 1;
+        self.cost += cost;
+
+        match (infected, positive) {
+            (true, true) => self.true_positives += 1,
+            (false, true) => self.false_positives += 1,
+            (false, false) => self.true_negatives += 1,
+            (true, false) => self.false_negatives += 1,
+        }
+    }
+}
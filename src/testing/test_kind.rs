@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{interpolate, validate_increasing},
+    CONFIG,
+};
+
+/// Chance of an infected entity testing positive, over the course of its infection.
+/// The course is given as progress from 0.0 (just infected) to 1.0 (end of the infection).
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SensitivityCurve {
+    Constant(f32),
+    /// Rises linearly from min at the start of the infection to max at progress peak,
+    /// declines linearly back to min at the end of the infection.
+    Peak {
+        min: f32,
+        max: f32,
+        peak: f32,
+    },
+    /// Points of (progress, sensitivity), linearly interpolated in between.
+    /// Sorted by progress, the first and last value are kept beyond them.
+    Table(Vec<(f32, f32)>),
+}
+
+impl SensitivityCurve {
+    pub fn at(&self, progress: f32) -> f32 {
+        match self {
+            SensitivityCurve::Constant(sensitivity) => *sensitivity,
+            SensitivityCurve::Peak { min, max, peak } => {
+                let factor = if progress < *peak {
+                    progress / peak.max(f32::EPSILON)
+                } else {
+                    (1.0 - progress) / (1.0 - peak).max(f32::EPSILON)
+                };
+                min + (max - min) * factor.clamp(0.0, 1.0)
+            }
+            SensitivityCurve::Table(points) => interpolate(points, progress),
+        }
+    }
+}
+
+/// A kind of test, e.g. a rapid antigen test or a PCR test.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TestKind {
+    pub name: String,
+    pub sensitivity: SensitivityCurve,
+    pub specificity: f32, // Chance of a non infected entity testing negative.
+    pub cost: f32,        // Cost of a single test.
+    pub delay: u32,       // Ticks until a positive result takes effect.
+    pub share: f32,       // Relative share of the tests performed with this kind.
+}
+
+impl TestKind {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.share.is_finite() || self.share < 0.0 {
+            return Err(format!(
+                "the share of the {} tests has to be positive or 0, got {}",
+                self.name, self.share
+            )
+            .into());
+        }
+
+        match &self.sensitivity {
+            SensitivityCurve::Table(points) => validate_increasing(
+                &format!("progresses of the {} sensitivity", self.name),
+                points,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the configured test kinds.
+    /// Without any configured kinds, a single instant test using
+    /// test_true_positive and test_true_negative is used.
+    pub fn configured() -> Vec<TestKind> {
+        if !CONFIG.core.test_kinds.is_empty() {
+            return CONFIG.core.test_kinds.clone();
+        }
+
+        vec![TestKind {
+            name: "default".to_string(),
+            sensitivity: SensitivityCurve::Constant(CONFIG.core.test_true_positive),
+            specificity: CONFIG.core.test_true_negative,
+            cost: 0.0,
+            delay: 0,
+            share: 1.0,
+        }]
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::{
+    distributions::WeightedIndex, prelude::Distribution, prelude::SliceRandom, rngs::StdRng, Rng,
+    SeedableRng,
+};
+
+use crate::{
+    entity::{Entity, EntityId, InfectionStatus},
+    util::UnsafeArray,
+    CONFIG,
+};
+
+use super::{TestCounts, TestKind, TestingStrategy};
+
+/// Selects and tests entities according to the testing strategy.
+/// Each test is of a random kind, drawn by the kinds shares.
+/// Positive results take effect once the delay of their kind has passed.
+pub struct Testing {
+    strategy: TestingStrategy,
+    kinds: Vec<TestKind>,
+    kind_index: Option<WeightedIndex<f32>>, // None if there is only a single kind.
+    pending: Vec<(u32, EntityId)>,          // Positive results and the tick they take effect.
+    awaiting: HashSet<EntityId>, // Entities with a pending result, which are not tested again.
+    cohort: Vec<EntityId>,       // Members of the cohort, only used by the cohort strategy.
+    next_in_cohort: usize,
+    contacts: VecDeque<EntityId>, // Traced contacts waiting to be tested.
+    queued: HashSet<EntityId>,    // The entities in contacts, to skip contacts traced twice.
+    counts: TestCounts,           // Tests of the last tick.
+
+    rng: StdRng,
+}
+
+impl Testing {
+    /// Creates the testing for the given population.
+    /// The cohort strategy draws its cohort here.
+    pub fn new(strategy: TestingStrategy, population: &[Entity]) -> Testing {
+        let mut rng = StdRng::from_entropy();
+
+        let cohort = match strategy {
+            TestingStrategy::Cohort { size } => population
+                .choose_multiple(&mut rng, size as usize)
+                .map(|entity| entity.id())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let kinds = TestKind::configured();
+        let kind_index = if kinds.len() > 1 {
+            Some(
+                WeightedIndex::new(kinds.iter().map(|kind| kind.share))
+                    .expect("The shares are validated with the config."),
+            )
+        } else {
+            None
+        };
+
+        Testing {
+            strategy,
+            kinds,
+            kind_index,
+            pending: Vec::new(),
+            awaiting: HashSet::new(),
+            cohort,
+            next_in_cohort: 0,
+            contacts: VecDeque::new(),
+            queued: HashSet::new(),
+            counts: TestCounts::default(),
+            rng,
+        }
+    }
+
+    /// Performs the tests of a single tick.
+    /// Returns the ids of the positive tested entities whose results take effect at the given time,
+    /// in the order they were tested. Results of entities that died in the meantime are dropped.
+    pub fn run(&mut self, population: &UnsafeArray<Entity>, time: u32) -> Vec<EntityId> {
+        self.counts = TestCounts::default();
+        let budget = CONFIG.core.tests_per_time as usize;
+
+        let tested = match self.strategy.clone() {
+            TestingStrategy::Random => self.sample(population, budget, |_| true),
+            TestingStrategy::Symptomatic => {
+                self.sample(population, budget, |entity| entity.is_symptomatic())
+            }
+            TestingStrategy::Targeted {
+                min_age,
+                max_age,
+                occupation,
+            } => self.sample(population, budget, |entity| {
+                (min_age..=max_age).contains(&entity.age())
+                    && occupation.is_none_or(|occupation| entity.occupation() == occupation)
+            }),
+            TestingStrategy::Contacts { .. } => {
+                let mut tested = Vec::with_capacity(budget);
+                while tested.len() < budget {
+                    let entity_id = match self.contacts.pop_front() {
+                        Some(entity_id) => entity_id,
+                        None => break,
+                    };
+                    self.queued.remove(&entity_id);
+
+                    if self.is_eligible(&population.get()[entity_id]) {
+                        tested.push(entity_id);
+                    }
+                }
+
+                let remaining = budget - tested.len();
+                tested.extend(self.sample(population, remaining, |_| true));
+                tested
+            }
+            TestingStrategy::Cohort { .. } => {
+                let mut tested = Vec::with_capacity(budget);
+                for _ in 0..self.cohort.len().min(budget) {
+                    let entity_id = self.cohort[self.next_in_cohort];
+                    self.next_in_cohort = (self.next_in_cohort + 1) % self.cohort.len();
+
+                    if self.is_eligible(&population.get()[entity_id]) {
+                        tested.push(entity_id);
+                    }
+                }
+                tested
+            }
+            TestingStrategy::Pooled { pool_size } => {
+                self.run_pooled(population, budget, pool_size.max(1) as usize, time);
+                Vec::new()
+            }
+        };
+
+        for entity_id in tested {
+            self.test(population.get_at_mut(entity_id), time);
+        }
+
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(due, _)| *due <= time);
+        self.pending = pending;
+        for (_, entity_id) in &due {
+            self.awaiting.remove(entity_id);
+        }
+
+        let positives: Vec<EntityId> = due
+            .into_iter()
+            .map(|(_, entity_id)| entity_id)
+            .filter(|entity_id| !population.get()[*entity_id].is_dead())
+            .collect();
+
+        self.counts.positives = positives.len() as u32;
+        positives
+    }
+
+    /// Tests random pools, members of positive pools are retested individually.
+    /// Both the pooled and the individual tests count towards the budget.
+    /// Pools are retested right away, only the individual results are delayed.
+    fn run_pooled(
+        &mut self,
+        population: &UnsafeArray<Entity>,
+        budget: usize,
+        pool_size: usize,
+        time: u32,
+    ) {
+        let mut candidates = self.sample(population, usize::MAX, |_| true);
+        candidates.shuffle(&mut self.rng);
+
+        for pool in candidates.chunks(pool_size) {
+            if self.counts.tests as usize >= budget {
+                break;
+            }
+
+            let members: Vec<&Entity> = pool
+                .iter()
+                .map(|entity_id| &population.get()[*entity_id])
+                .collect();
+
+            if !self.test_pool(&members) {
+                continue;
+            }
+
+            for entity_id in pool {
+                if self.counts.tests as usize >= budget {
+                    break;
+                }
+
+                self.test(population.get_at_mut(*entity_id), time);
+            }
+        }
+    }
+
+    /// Returns the radius in which contacts of positive tested entities are traced,
+    /// None if the strategy does not trace contacts.
+    pub fn tracing_radius(&self) -> Option<f32> {
+        match self.strategy {
+            TestingStrategy::Contacts { radius } => Some(radius),
+            _ => None,
+        }
+    }
+
+    /// Queues the given contacts of a positive tested entity for testing.
+    pub fn trace(&mut self, contacts: impl Iterator<Item = EntityId>) {
+        for entity_id in contacts {
+            if !self.awaiting.contains(&entity_id) && self.queued.insert(entity_id) {
+                self.contacts.push_back(entity_id);
+            }
+        }
+    }
+
+    /// Returns the tests performed during the last tick.
+    pub fn counts(&self) -> TestCounts {
+        self.counts
+    }
+
+    /// Draws up to count distinct testable entities matching the filter.
+    fn sample(
+        &mut self,
+        population: &UnsafeArray<Entity>,
+        count: usize,
+        filter: impl Fn(&Entity) -> bool,
+    ) -> Vec<EntityId> {
+        let candidates: Vec<EntityId> = population
+            .get()
+            .iter()
+            .filter(|entity| self.is_eligible(entity) && filter(entity))
+            .map(|entity| entity.id())
+            .collect();
+
+        candidates
+            .choose_multiple(&mut self.rng, count)
+            .copied()
+            .collect()
+    }
+
+    /// Draws the kind of the next test.
+    fn kind(&mut self) -> usize {
+        match &self.kind_index {
+            Some(kind_index) => kind_index.sample(&mut self.rng),
+            None => 0,
+        }
+    }
+
+    /// Tests a single entity and records the result.
+    /// A positive result is queued until the delay of the test kind has passed.
+    fn test(&mut self, entity: &mut Entity, time: u32) {
+        let kind = self.kind();
+        let kind = &self.kinds[kind];
+        let positive = entity.test(kind);
+        self.counts
+            .record(Testing::is_infected(entity), positive, kind.cost);
+
+        if positive {
+            self.pending.push((time + kind.delay, entity.id()));
+            self.awaiting.insert(entity.id());
+        }
+    }
+
+    /// Tests a pool of entities as a whole, the pool is infected if any member is.
+    /// An infected pool is detected with the sensitivity of its furthest progressed infection.
+    fn test_pool(&mut self, members: &[&Entity]) -> bool {
+        let kind = self.kind();
+        let kind = &self.kinds[kind];
+
+        let progress = members
+            .iter()
+            .filter(|entity| Testing::is_infected(entity))
+            .map(|entity| entity.infection_progress())
+            .reduce(f32::max);
+
+        let rng = self.rng.gen::<f32>();
+        let positive = match progress {
+            Some(progress) => rng < kind.sensitivity.at(progress),
+            None => rng >= kind.specificity,
+        };
+
+        self.counts.record(progress.is_some(), positive, kind.cost);
+        positive
+    }
+
+    /// Returns true if the entity can be tested, i.e. it is neither dead, hospitalized
+    /// nor awaiting a positive result.
+    fn is_eligible(&self, entity: &Entity) -> bool {
+        !entity.is_dead() && !entity.is_hospitalized() && !self.awaiting.contains(&entity.id())
+    }
+
+    fn is_infected(entity: &Entity) -> bool {
+        matches!(entity.status(), InfectionStatus::Infected(_))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::entity::EntityId;
+
+/// A single infection of an entity.
+pub struct Infection {
+    pub entity: EntityId,
+    pub time: u32,
+    pub generation_interval: Option<u32>, // Ticks between the infection of the infector and this infection.
+    pub offspring: u32,                   // Number of entities infected by this infection.
+}
+
+/// Records who infected whom, to derive offspring distributions and generation intervals.
+pub struct Transmissions {
+    infections: Vec<Infection>,
+    current: Vec<Option<usize>>, // Index of the latest infection of each entity.
+}
+
+impl Transmissions {
+    pub fn new(population_size: usize) -> Transmissions {
+        Transmissions {
+            infections: Vec::new(),
+            current: vec![None; population_size],
+        }
+    }
+
+    /// Records the infection of an entity at the given time.
+    /// Counts it towards the offspring of the infectors latest infection.
+    /// The infector is None for infections without a known source, e.g. those present at the start.
+    pub fn record(&mut self, entity: EntityId, infector: Option<EntityId>, time: u32) {
+        // Entities born during the simulation are not known yet.
+        if entity >= self.current.len() {
+            self.current.resize(entity + 1, None);
+        }
+
+        let source = infector.and_then(|infector| self.current[infector]);
+
+        let generation_interval = source.map(|source| {
+            let source = &mut self.infections[source];
+            source.offspring += 1;
+            time - source.time
+        });
+
+        self.current[entity] = Some(self.infections.len());
+        self.infections.push(Infection {
+            entity,
+            time,
+            generation_interval,
+            offspring: 0,
+        });
+    }
+
+    /// Returns all recorded infections in the order they occurred.
+    pub fn infections(&self) -> &Vec<Infection> {
+        &self.infections
+    }
+
+    /// Returns true if the given infection is the latest infection of its entity.
+    pub fn is_latest(&self, index: usize) -> bool {
+        self.current[self.infections[index].entity] == Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_a_transmission_chain() {
+        let mut transmissions = Transmissions::new(3);
+        transmissions.record(0, None, 0);
+        transmissions.record(1, Some(0), 4);
+        transmissions.record(2, Some(1), 9);
+        transmissions.record(0, Some(2), 15);
+        // Entities born during the simulation are added on their first infection.
+        transmissions.record(4, Some(0), 18);
+
+        let infections = transmissions.infections();
+        let offspring: Vec<u32> = infections
+            .iter()
+            .map(|infection| infection.offspring)
+            .collect();
+        let intervals: Vec<Option<u32>> = infections
+            .iter()
+            .map(|infection| infection.generation_interval)
+            .collect();
+
+        assert_eq!(offspring, vec![1, 1, 1, 1, 0]);
+        assert_eq!(intervals, vec![None, Some(4), Some(5), Some(6), Some(3)]);
+        assert!(!transmissions.is_latest(0));
+        assert!((1..5).all(|index| transmissions.is_latest(index)));
+    }
+}
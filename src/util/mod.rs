@@ -1,5 +1,7 @@
+mod poisson;
 mod progress_bar;
 mod unsafe_array;
 
+pub use poisson::sample_poisson;
 pub use progress_bar::print_progress;
 pub use unsafe_array::UnsafeArray;
@@ -0,0 +1,10 @@
+use rand::Rng;
+use rand_distr::{Distribution, Poisson};
+
+/// Samples a Poisson distributed number of events with the given mean, 0 if the mean isn't positive.
+pub fn sample_poisson(mean: f64, rng: &mut impl Rng) -> u64 {
+    match Poisson::new(mean) {
+        Ok(poisson) => poisson.sample(rng) as u64,
+        Err(_) => 0,
+    }
+}
@@ -22,7 +22,8 @@ impl<T> UnsafeArray<T> {
         unsafe { &mut (*self.0.get())[index] }
     }
 
-    pub fn len(&self) -> usize {
-        unsafe { (*self.0.get()).len() }
+    /// Appends an element. Requires exclusive access, so no other thread can observe the reallocation.
+    pub fn push(&mut self, value: T) {
+        self.0.get_mut().push(value);
     }
 }